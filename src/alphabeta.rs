@@ -0,0 +1,123 @@
+use crate::board::{Board, GameBoard, Player};
+use crate::eval::{self, WIN_SCORE};
+
+#[cfg(test)]
+mod tests;
+
+/// Only this many of the best ordered moves are searched at each node.
+const MAX_BRANCHING: usize = 12;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Evaluation {
+    /// For the player to move; `WIN_SCORE` and above is a forced win
+    pub score: i32,
+    pub best_move: Option<i16>,
+    pub nodes: u64,
+}
+
+/// Depth-limited negamax with alpha-beta pruning over the pattern
+/// evaluator. Moves are ordered by `eval::score_move` and only the most
+/// promising are tried, so unlike `Solver` it scales to large boards but
+/// can miss moves.
+pub struct AlphaBeta {
+    depth: u32,
+    nodes: u64,
+}
+
+impl AlphaBeta {
+    pub fn new(depth: u32) -> Self {
+        Self { depth, nodes: 0 }
+    }
+
+    pub fn search(&mut self, board: &Board, player: Player) -> Evaluation {
+        let mut board = board.clone();
+        board.set_to_move(player);
+        self.nodes = 0;
+
+        let (score, best_move) = self.negamax(
+            &mut board,
+            player,
+            self.depth,
+            -WIN_SCORE * 2,
+            WIN_SCORE * 2,
+        );
+
+        Evaluation {
+            score,
+            best_move,
+            nodes: self.nodes,
+        }
+    }
+
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        player: Player,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> (i32, Option<i16>) {
+        self.nodes += 1;
+
+        // Quicker wins score higher, by the depth left to search.
+        if let Some(&m) = board.winning_moves(player).first() {
+            return (WIN_SCORE + depth as i32 + 1, Some(m));
+        }
+
+        if board.is_tie() {
+            return (0, None);
+        }
+
+        let threats = board.winning_moves(player.next());
+        if threats.len() > 1 {
+            return (-WIN_SCORE - depth as i32, Some(threats[0]));
+        }
+
+        if depth == 0 {
+            return (eval::evaluate(board, player), None);
+        }
+
+        let moves = if threats.is_empty() {
+            ordered_moves(board, player)
+        } else {
+            threats
+        };
+
+        let mut best = (-WIN_SCORE * 2, None);
+        for m in moves {
+            board
+                .apply_move(m, player)
+                .expect("legal move should be valid");
+            let (value, _) = self.negamax(board, player.next(), depth - 1, -beta, -alpha);
+            board.undo_move(m).expect("undo should be valid");
+
+            let value = -value;
+            if value > best.0 {
+                best = (value, Some(m));
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best
+    }
+}
+
+/// The most promising moves first, the centre first among equals.
+fn ordered_moves(board: &Board, player: Player) -> Vec<i16> {
+    let n = board.n();
+    let centre = n - 1;
+    let mut moves = GameBoard::legal_moves(board);
+    moves.sort_by_cached_key(|&m| {
+        let (x, y) = (m % n, m / n);
+        (
+            std::cmp::Reverse(eval::score_move(board, m, player)),
+            (2 * x - centre).abs() + (2 * y - centre).abs(),
+        )
+    });
+    moves.truncate(MAX_BRANCHING);
+
+    moves
+}
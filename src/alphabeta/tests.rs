@@ -0,0 +1,46 @@
+use crate::alphabeta::AlphaBeta;
+use crate::board::{Board, Player};
+use crate::eval::WIN_SCORE;
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_win_and_block() {
+        let mut board = Board::new(9, 4);
+        for (m, p) in [
+            (10, Player::X),
+            (50, Player::O),
+            (11, Player::X),
+            (51, Player::O),
+            (12, Player::X),
+        ] {
+            board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let evaluation = AlphaBeta::new(2).search(&board, Player::O);
+        assert!(evaluation.best_move == Some(9) || evaluation.best_move == Some(13));
+
+        let evaluation = AlphaBeta::new(2).search(&board, Player::X);
+        assert!(evaluation.score > WIN_SCORE);
+        assert!(evaluation.best_move == Some(9) || evaluation.best_move == Some(13));
+    }
+
+    #[test]
+    fn sees_open_three_coming() {
+        // X to move makes an open three on a wide board, which O can't stop.
+        let mut board = Board::new(9, 4);
+        for (m, p) in [
+            (39, Player::X),
+            (0, Player::O),
+            (40, Player::X),
+            (8, Player::O),
+        ] {
+            board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let evaluation = AlphaBeta::new(4).search(&board, Player::X);
+        assert!(evaluation.score > WIN_SCORE);
+        assert!(evaluation.nodes > 1);
+    }
+}
@@ -0,0 +1,292 @@
+use std::error::Error;
+use std::ops::{BitAnd, BitOr, Not, Shr};
+
+use crate::board::board::GameError;
+use crate::board::{Board, GameBoard, Player};
+
+#[cfg(test)]
+mod tests;
+
+/// A fixed-width bitset with one bit per cell, cell `m` being bit `m`.
+pub trait Bits:
+    Copy
+    + PartialEq
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + Not<Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const CAPACITY: usize;
+    const ZERO: Self;
+
+    fn bit(i: usize) -> Self;
+    fn count_ones(self) -> u32;
+    fn trailing_zeros(self) -> u32;
+    fn clear_lowest(self) -> Self;
+
+    fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+
+    fn has(self, i: usize) -> bool {
+        !(self & Self::bit(i)).is_zero()
+    }
+}
+
+impl Bits for u128 {
+    const CAPACITY: usize = 128;
+    const ZERO: Self = 0;
+
+    fn bit(i: usize) -> Self {
+        1 << i
+    }
+
+    fn count_ones(self) -> u32 {
+        u128::count_ones(self)
+    }
+
+    fn trailing_zeros(self) -> u32 {
+        u128::trailing_zeros(self)
+    }
+
+    fn clear_lowest(self) -> Self {
+        self & self.wrapping_sub(1)
+    }
+}
+
+/// A 256-bit set stored as four little-endian words, enough for 16x16.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct U256([u64; 4]);
+
+impl BitAnd for U256 {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        U256(std::array::from_fn(|i| self.0[i] & rhs.0[i]))
+    }
+}
+
+impl BitOr for U256 {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        U256(std::array::from_fn(|i| self.0[i] | rhs.0[i]))
+    }
+}
+
+impl Not for U256 {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        U256(self.0.map(|w| !w))
+    }
+}
+
+impl Shr<u32> for U256 {
+    type Output = Self;
+
+    fn shr(self, s: u32) -> Self {
+        let words = (s / 64) as usize;
+        let bits = s % 64;
+
+        U256(std::array::from_fn(|i| {
+            let lo = self.0.get(i + words).copied().unwrap_or(0);
+            let hi = self.0.get(i + words + 1).copied().unwrap_or(0);
+            if bits == 0 {
+                lo
+            } else {
+                (lo >> bits) | (hi << (64 - bits))
+            }
+        }))
+    }
+}
+
+impl Bits for U256 {
+    const CAPACITY: usize = 256;
+    const ZERO: Self = U256([0; 4]);
+
+    fn bit(i: usize) -> Self {
+        let mut words = [0; 4];
+        words[i / 64] = 1 << (i % 64);
+        U256(words)
+    }
+
+    fn count_ones(self) -> u32 {
+        self.0.iter().map(|w| w.count_ones()).sum()
+    }
+
+    fn trailing_zeros(self) -> u32 {
+        for (i, w) in self.0.iter().enumerate() {
+            if *w != 0 {
+                return i as u32 * 64 + w.trailing_zeros();
+            }
+        }
+
+        256
+    }
+
+    fn clear_lowest(self) -> Self {
+        let mut words = self.0;
+        if let Some(w) = words.iter_mut().find(|w| **w != 0) {
+            *w &= *w - 1;
+        }
+        U256(words)
+    }
+}
+
+/// Board backend keeping one bitset per player. Lines are found by shifting
+/// the player's stones along each direction, so it only fits boards with at
+/// most `B::CAPACITY` cells.
+#[derive(Clone)]
+pub struct BitBoard<B: Bits> {
+    n: i16,
+    k: i8,
+    stones: [B; 2],
+    full: B,
+    starts: [B; 4],
+    steps: [u32; 4],
+    last_move: Option<i16>,
+}
+
+impl<B: Bits> BitBoard<B> {
+    pub fn fits(n: i16) -> bool {
+        n > 0 && (n as usize) * (n as usize) <= B::CAPACITY
+    }
+
+    #[allow(dead_code)]
+    pub fn new(n: i16, k: i8) -> Self {
+        BitBoard::new_from_state(n, k, vec![None; (n * n) as usize])
+    }
+
+    pub fn new_from_state(n: i16, k: i8, cells: Vec<Option<Player>>) -> Self {
+        if !Self::fits(n) || cells.len() != (n * n) as usize {
+            panic!("invalid state loaded")
+        }
+
+        let span = k.max(1) as i16 - 1;
+        let dirs: [(i16, i16); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+        let mut full = B::ZERO;
+        let mut stones = [B::ZERO; 2];
+        let mut starts = [B::ZERO; 4];
+
+        for (m, cell) in cells.iter().enumerate() {
+            full = full | B::bit(m);
+            if let Some(p) = cell {
+                stones[p.index()] = stones[p.index()] | B::bit(m);
+            }
+
+            let (x, y) = (m as i16 % n, m as i16 / n);
+            for (d, (dx, dy)) in dirs.iter().enumerate() {
+                let (end_x, end_y) = (x + dx * span, y + dy * span);
+                if end_x >= 0 && end_x < n && end_y < n {
+                    starts[d] = starts[d] | B::bit(m);
+                }
+            }
+        }
+
+        let n_u = n as u32;
+        Self {
+            n,
+            k,
+            stones,
+            full,
+            starts,
+            steps: [1, n_u, n_u + 1, n_u - 1],
+            last_move: None,
+        }
+    }
+
+    fn has_line(&self, player: Player) -> bool {
+        let stones = self.stones[player.index()];
+
+        self.starts.iter().zip(self.steps).any(|(&start, step)| {
+            let mut run = stones & start;
+            for i in 1..self.k.max(1) as u32 {
+                if run.is_zero() {
+                    return false;
+                }
+                run = run & (stones >> (step * i));
+            }
+
+            !run.is_zero()
+        })
+    }
+
+    fn empty(&self) -> B {
+        self.full & !(self.stones[0] | self.stones[1])
+    }
+}
+
+/// Only freestyle lines are detected, so this drops an exact-k variant.
+impl<B: Bits> From<&Board> for BitBoard<B> {
+    fn from(board: &Board) -> Self {
+        BitBoard::new_from_state(board.n(), board.k(), board.cells().to_vec())
+    }
+}
+
+impl<B: Bits> GameBoard for BitBoard<B> {
+    fn n(&self) -> i16 {
+        self.n
+    }
+
+    fn k(&self) -> i8 {
+        self.k
+    }
+
+    fn cell(&self, m: i16) -> Option<Player> {
+        if self.stones[0].has(m as usize) {
+            Some(Player::X)
+        } else if self.stones[1].has(m as usize) {
+            Some(Player::O)
+        } else {
+            None
+        }
+    }
+
+    fn legal_moves(&self) -> Vec<i16> {
+        let mut empty = self.empty();
+        let mut moves = Vec::with_capacity(empty.count_ones() as usize);
+        while !empty.is_zero() {
+            moves.push(empty.trailing_zeros() as i16);
+            empty = empty.clear_lowest();
+        }
+
+        moves
+    }
+
+    fn is_tie(&self) -> bool {
+        self.empty().is_zero()
+    }
+
+    fn apply_move(&mut self, m: i16, player: Player) -> Result<Option<Player>, Box<dyn Error>> {
+        if m < 0 || m >= self.n * self.n || self.cell(m).is_some() {
+            return Err(Box::from(GameError::InvalidMove));
+        }
+
+        let p = player.index();
+        self.stones[p] = self.stones[p] | B::bit(m as usize);
+        self.last_move = Some(m);
+
+        Ok(self.check_winner_from(m))
+    }
+
+    fn undo_last_move(&mut self) -> Result<(), Box<dyn Error>> {
+        let last_move = match self.last_move {
+            Some(m) => m,
+            None => return Err(Box::from(GameError::InvalidMove)),
+        };
+
+        let mask = !B::bit(last_move as usize);
+        self.stones = self.stones.map(|s| s & mask);
+        self.last_move = None;
+
+        Ok(())
+    }
+
+    /// Unlike `Board`, this reports any completed line of the player at `m`,
+    /// which is the same thing as long as the game was undecided before `m`.
+    fn check_winner_from(&self, m: i16) -> Option<Player> {
+        let p = self.cell(m)?;
+        if self.has_line(p) { Some(p) } else { None }
+    }
+}
@@ -0,0 +1,93 @@
+use rand::random_range;
+
+use crate::board;
+use crate::board::bitboard::Bits;
+use crate::board::{BitBoard, Board, GameBoard, Player, U256};
+
+mod tests {
+    use super::*;
+
+    fn random_game<B: Bits>(n: i16, k: i8) {
+        let mut reference = Board::new(n, k);
+        let mut bits = BitBoard::<B>::new(n, k);
+        let mut player = Player::X;
+
+        loop {
+            let moves = GameBoard::legal_moves(&reference);
+            assert_eq!(moves, bits.legal_moves());
+            if moves.is_empty() {
+                assert!(bits.is_tie());
+                return;
+            }
+
+            let m = moves[random_range(0..moves.len())];
+            let expected = reference
+                .apply_move(m, player)
+                .expect("move should be valid");
+            let winner = bits.apply_move(m, player).expect("move should be valid");
+            assert_eq!(expected, winner);
+
+            if winner.is_some() {
+                return;
+            }
+
+            player = player.next();
+        }
+    }
+
+    #[test]
+    fn matches_board_u128() {
+        for _ in 0..200 {
+            random_game::<u128>(3, 3);
+            random_game::<u128>(7, 4);
+            random_game::<u128>(11, 5);
+        }
+    }
+
+    #[test]
+    fn matches_board_u256() {
+        for _ in 0..200 {
+            random_game::<U256>(12, 5);
+            random_game::<U256>(16, 5);
+        }
+    }
+
+    #[test]
+    fn diagonal_lines() {
+        let board = "
+        [ ][ ][ ][O]
+        [ ][ ][O][ ]
+        [ ][ ][ ][ ]
+        [O][ ][ ][X]
+        ";
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
+        let mut test_board = BitBoard::<u128>::new_from_state(n as i16, 4, cells);
+
+        assert_eq!(
+            test_board
+                .apply_move(9, Player::X)
+                .expect("move should be valid"),
+            None
+        );
+        test_board.undo_last_move().expect("undo should be valid");
+        assert_eq!(
+            test_board
+                .apply_move(9, Player::O)
+                .expect("move should be valid"),
+            Some(Player::O)
+        );
+    }
+
+    #[test]
+    fn undo_last_move() {
+        let mut test_board = BitBoard::<U256>::new(16, 5);
+        test_board
+            .apply_move(200, Player::O)
+            .expect("move should be valid");
+        assert_eq!(test_board.cell(200), Some(Player::O));
+
+        test_board.undo_last_move().expect("undo should be valid");
+        assert_eq!(test_board.cell(200), None);
+        assert!(test_board.undo_last_move().is_err());
+    }
+}
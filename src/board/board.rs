@@ -1,6 +1,15 @@
 use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
 
 use crate::board::Player;
+use crate::board::lines::Lines;
+use crate::board::render::Renderer;
+use crate::board::symmetry::Symmetry;
+use crate::board::zobrist;
+
+#[cfg(test)]
+mod tests;
 
 #[derive(strum_macros::Display, Debug)]
 pub enum GameError {
@@ -9,79 +18,226 @@ pub enum GameError {
 
 impl Error for GameError {}
 
+/// Why a position in one of the text formats could not be read.
+#[derive(strum_macros::Display, Debug)]
+pub enum StateError {
+    InvalidCell,
+    NotSquare,
+    TooLarge,
+}
+
+impl Error for StateError {}
+
+/// The largest board whose cells can all be indexed with an `i16`.
+pub const MAX_N: usize = 181;
+
+/// Why a board size and row length don't make a game.
+#[derive(strum_macros::Display, Debug)]
+pub enum RulesError {
+    SizeOutOfRange,
+    RowLengthOutOfRange,
+}
+
+impl Error for RulesError {}
+
+/// Checks that an `n`x`n` board is at most `MAX_N` wide and that a row of
+/// `k` fits on it.
+pub fn check_rules(n: i16, k: i8) -> Result<(), RulesError> {
+    if n < 1 || n as usize > MAX_N {
+        return Err(RulesError::SizeOutOfRange);
+    }
+    if k < 1 || k as i16 > n {
+        return Err(RulesError::RowLengthOutOfRange);
+    }
+
+    Ok(())
+}
+
+/// Which lines win the game.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Variant {
+    /// k or more in a row wins
+    #[default]
+    Freestyle,
+    /// Exactly k in a row wins, longer lines (overlines) do not
+    Exact,
+}
+
+/// The operations shared by every board backend, so that hot loops such as
+/// rollouts can run on whichever representation is fastest for the size.
+#[allow(dead_code)]
+pub trait GameBoard: Clone {
+    fn n(&self) -> i16;
+    fn k(&self) -> i8;
+    fn cell(&self, m: i16) -> Option<Player>;
+    fn legal_moves(&self) -> Vec<i16>;
+    fn is_tie(&self) -> bool;
+    fn apply_move(&mut self, m: i16, player: Player) -> Result<Option<Player>, Box<dyn Error>>;
+    fn undo_last_move(&mut self) -> Result<(), Box<dyn Error>>;
+    fn check_winner_from(&self, m: i16) -> Option<Player>;
+}
+
 #[derive(Clone)]
 
 pub struct Board {
     n: i16,
     k: i8,
+    variant: Variant,
     cells: Vec<Option<Player>>,
     last_move: Option<i16>,
-    x_list: Vec<i16>,
-    y_list: Vec<i16>,
+    lines: Arc<Lines>,
+    window_counts: Vec<[u8; 2]>,
+    open_windows: Vec<[u32; 2]>,
+    to_move: Player,
+    hash: u64,
 }
 
-struct Dir {
-    dx: i16,
-    dy: i16,
-}
-
-const DIRECTIONS: [Dir; 4] = [
-    Dir { dx: 1, dy: 0 },
-    Dir { dx: 0, dy: 1 },
-    Dir { dx: 1, dy: 1 },
-    Dir { dx: 1, dy: -1 },
-];
-
 impl Board {
     pub fn new(n: i16, k: i8) -> Self {
         Board::new_from_state(n, k, vec![None; (n * n) as usize])
     }
 
+    /// An empty board with `moves` played on it in order, for tests.
+    #[cfg(test)]
+    pub fn from_moves(n: i16, k: i8, moves: &[(i16, Player)]) -> Self {
+        let mut board = Board::new(n, k);
+        for &(m, p) in moves {
+            board.apply_move(m, p).expect("move should be valid");
+        }
+        board
+    }
+
     pub fn new_from_state(n: i16, k: i8, cells: Vec<Option<Player>>) -> Self {
         if cells.len() != (n * n) as usize {
             panic!("invalid state loaded")
         }
 
-        let x_list: Vec<i16> = (0..n * n).map(|i| i % n).collect();
-        let y_list: Vec<i16> = (0..n * n).map(|i| i / n).collect();
-        Self {
-            n: n,
-            k: k,
-            cells: cells,
+        let lines = Arc::new(Lines::new(n, k));
+        let mut open_windows = vec![[0, 0]; k.max(1) as usize + 1];
+        open_windows[0] = [lines.count() as u32; 2];
+
+        let mut board = Self {
+            n,
+            k,
+            variant: Variant::Freestyle,
+            cells: vec![None; cells.len()],
             last_move: None,
-            x_list: x_list,
-            y_list: y_list,
+            window_counts: vec![[0, 0]; lines.count()],
+            lines,
+            open_windows,
+            to_move: Player::X,
+            hash: zobrist::rules_key(n, k),
+        };
+
+        let mut stones = [0, 0];
+        for (m, cell) in cells.into_iter().enumerate() {
+            if let Some(p) = cell {
+                board.place(m as i16, p);
+                stones[p.index()] += 1;
+            }
         }
+
+        if stones[0] > stones[1] {
+            board.set_to_move(Player::O);
+        }
+
+        board
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        if self.variant != variant {
+            self.hash ^= zobrist::EXACT_KEY;
+            self.variant = variant;
+        }
+        self
     }
 
     pub fn cells(&self) -> &[Option<Player>] {
         &self.cells
     }
 
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
     pub fn n(&self) -> i16 {
         self.n
     }
 
+    pub fn k(&self) -> i8 {
+        self.k
+    }
+
+    /// The player expected to move next: the opponent of whoever moved last,
+    /// or for a loaded position, O when X has more stones and X otherwise.
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn set_to_move(&mut self, player: Player) {
+        if self.to_move != player {
+            self.hash ^= zobrist::SIDE_KEY;
+            self.to_move = player;
+        }
+    }
+
+    /// 64-bit Zobrist hash of the stones, the rules and the side to move,
+    /// kept up to date on every move and undo.
+    #[allow(dead_code)]
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    /// A copy of the board with every stone moved by `symmetry`. Only
+    /// meaningful for the square boards this type always holds.
+    #[allow(dead_code)]
+    pub fn transformed(&self, symmetry: Symmetry) -> Board {
+        let mut cells = vec![None; self.cells.len()];
+        for (m, cell) in self.cells.iter().enumerate() {
+            cells[symmetry.apply(m as i16, self.n) as usize] = *cell;
+        }
+
+        let mut board = Board::new_from_state(self.n, self.k, cells).with_variant(self.variant);
+        board.set_to_move(self.to_move);
+        board.last_move = self.last_move.map(|m| symmetry.apply(m, self.n));
+        board
+    }
+
     pub fn is_tie(&self) -> bool {
         !self.cells.iter().any(|e| e.is_none())
     }
 
-    pub fn print(&self) {
-        for (i, cell) in self.cells.iter().enumerate() {
-            print!(
-                "[{}]",
-                match cell {
-                    Some(cell) => cell.pretty(),
-                    None => " ".to_string(),
-                }
-            );
+    pub fn last_move(&self) -> Option<i16> {
+        self.last_move
+    }
 
-            if (i + 1) % (self.n as usize) == 0 {
-                print!("\n")
+    /// Every cell that is part of a completed line, ascending.
+    pub fn winning_line(&self) -> Vec<i16> {
+        let mut cells: Vec<i16> = vec![];
+        for (w, counts) in self.window_counts.iter().enumerate() {
+            if !counts.iter().any(|&c| c as i8 >= self.k) {
+                continue;
             }
+
+            let window = self.lines.cells(w);
+            if self.variant == Variant::Exact {
+                let (dx, dy) = self.lines.direction(w);
+                let (first, last) = (window[0], window[window.len() - 1]);
+                let p = self.cells[first as usize].expect("full window");
+                if self.run_length(first, p, -dx, -dy) > 0 || self.run_length(last, p, dx, dy) > 0 {
+                    continue;
+                }
+            }
+
+            cells.extend(window);
         }
 
-        print!("\n")
+        cells.sort_unstable();
+        cells.dedup();
+        cells
     }
 
     pub fn apply_move(&mut self, m: i16, player: Player) -> Result<Option<Player>, Box<dyn Error>> {
@@ -91,13 +247,30 @@ impl Board {
         Ok(winner)
     }
 
+    #[allow(dead_code)]
     pub fn undo_last_move(&mut self) -> Result<(), Box<dyn Error>> {
         let last_move = match self.last_move {
             Some(m) => m,
             None => return Err(Box::from(GameError::InvalidMove)),
         };
 
-        self.cells[last_move as usize] = None;
+        if let Some(p) = self.cells[last_move as usize] {
+            self.set_to_move(p);
+        }
+        self.remove(last_move);
+        self.last_move = None;
+
+        Ok(())
+    }
+
+    /// Takes the stone at `m` back, whenever it was played. Searches that
+    /// apply and undo moves depth-first use this instead of `undo_last_move`,
+    /// which only remembers a single move.
+    pub fn undo_move(&mut self, m: i16) -> Result<(), Box<dyn Error>> {
+        let player = self.cells[m as usize].ok_or(GameError::InvalidMove)?;
+
+        self.set_to_move(player);
+        self.remove(m);
         self.last_move = None;
 
         Ok(())
@@ -108,12 +281,52 @@ impl Board {
             return Err(Box::from(GameError::InvalidMove));
         }
 
-        self.cells[m as usize] = Some(player);
+        self.place(m, player);
         self.last_move = Some(m);
+        self.set_to_move(player.next());
 
         Ok(())
     }
 
+    fn place(&mut self, m: i16, player: Player) {
+        self.cells[m as usize] = Some(player);
+        self.hash ^= zobrist::cell_key(m, player);
+
+        let (p, q) = (player.index(), player.next().index());
+        for &w in self.lines.windows_of(m) {
+            let counts = &mut self.window_counts[w as usize];
+            if counts[q] == 0 {
+                self.open_windows[counts[p] as usize][p] -= 1;
+                self.open_windows[counts[p] as usize + 1][p] += 1;
+            }
+            if counts[p] == 0 {
+                self.open_windows[counts[q] as usize][q] -= 1;
+            }
+            counts[p] += 1;
+        }
+    }
+
+    fn remove(&mut self, m: i16) {
+        let player = match self.cells[m as usize].take() {
+            Some(p) => p,
+            None => return,
+        };
+        self.hash ^= zobrist::cell_key(m, player);
+
+        let (p, q) = (player.index(), player.next().index());
+        for &w in self.lines.windows_of(m) {
+            let counts = &mut self.window_counts[w as usize];
+            counts[p] -= 1;
+            if counts[q] == 0 {
+                self.open_windows[counts[p] as usize + 1][p] -= 1;
+                self.open_windows[counts[p] as usize][p] += 1;
+            }
+            if counts[p] == 0 {
+                self.open_windows[counts[q] as usize][q] += 1;
+            }
+        }
+    }
+
     pub fn check_winner(&self) -> Option<Player> {
         let last_move = match self.last_move {
             Some(m) => m,
@@ -124,101 +337,306 @@ impl Board {
     }
 
     pub fn check_winner_from(&self, m: i16) -> Option<Player> {
-        let p = self.cells[m as usize];
-        if p.is_none() {
-            return None;
+        let p = self.cells[m as usize]?;
+
+        if self.variant == Variant::Exact {
+            return if self.exact_line_through(m, p) {
+                Some(p)
+            } else {
+                None
+            };
         }
 
-        let x: i16;
-        let y: i16;
-        unsafe {
-            x = *self.x_list.get_unchecked(m as usize);
-            y = *self.y_list.get_unchecked(m as usize);
+        let full = self
+            .lines
+            .windows_of(m)
+            .iter()
+            .any(|&w| self.window_counts[w as usize][p.index()] as i8 >= self.k);
+
+        if full { Some(p) } else { None }
+    }
+
+    /// The number of windows holding exactly `stones` of `player`'s stones and
+    /// none of the opponent's. `stones == k - 1` counts immediate wins.
+    pub fn threat_count(&self, player: Player, stones: i8) -> u32 {
+        if stones < 0 || stones > self.k {
+            return 0;
+        }
+
+        self.open_windows[stones as usize][player.index()]
+    }
+
+    #[allow(dead_code)]
+    pub fn has_winning_move(&self, player: Player) -> bool {
+        match self.variant {
+            Variant::Freestyle => self.threat_count(player, self.k - 1) > 0,
+            Variant::Exact => !self.winning_moves(player).is_empty(),
         }
+    }
 
-        for d in DIRECTIONS {
-            let mut count = 1;
+    /// The empty cells that would complete a k-window for `player`, ascending.
+    pub fn winning_moves(&self, player: Player) -> Vec<i16> {
+        let mut moves = vec![];
+        if self.threat_count(player, self.k - 1) == 0 {
+            return moves;
+        }
 
-            for dir_mod in [-1, 1] {
-                let dx_dir_mod = d.dx * dir_mod;
-                let dy_dir_mod = d.dy * dir_mod;
+        let (p, q) = (player.index(), player.next().index());
+        for (w, counts) in self.window_counts.iter().enumerate() {
+            if counts[p] as i8 != self.k - 1 || counts[q] != 0 {
+                continue;
+            }
 
-                let mut nx = x + dx_dir_mod;
-                let mut ny = y + dy_dir_mod;
+            if let Some(&m) = self
+                .lines
+                .cells(w)
+                .iter()
+                .find(|&&m| self.cells[m as usize].is_none())
+            {
+                moves.push(m);
+            }
+        }
 
-                while nx >= 0 && ny >= 0 && nx < self.n && ny < self.n {
-                    let n_idx = ny * self.n + nx;
-                    unsafe {
-                        if *self.cells.get_unchecked(n_idx as usize) != p {
-                            break;
-                        }
-                    }
+        if self.variant == Variant::Exact {
+            moves.retain(|&m| self.exact_line_through(m, player));
+        }
+        moves.sort_unstable();
+        moves.dedup();
+        moves
+    }
 
-                    count += 1;
-                    if count >= self.k {
-                        return p;
-                    }
+    /// The empty cells of every k-window holding exactly `stones` of
+    /// `player`'s stones and none of the opponent's, ascending. With
+    /// `stones == k - 2` these are the moves that make a four.
+    pub fn threat_moves(&self, player: Player, stones: i8) -> Vec<i16> {
+        let mut moves = vec![];
+        if self.threat_count(player, stones) == 0 {
+            return moves;
+        }
 
-                    nx += dx_dir_mod;
-                    ny += dy_dir_mod;
-                }
+        let (p, q) = (player.index(), player.next().index());
+        for (w, counts) in self.window_counts.iter().enumerate() {
+            if counts[p] as i8 != stones || counts[q] != 0 {
+                continue;
             }
+
+            moves.extend(
+                self.lines
+                    .cells(w)
+                    .iter()
+                    .filter(|&&m| self.cells[m as usize].is_none()),
+            );
         }
 
-        None
+        moves.sort_unstable();
+        moves.dedup();
+        moves
+    }
+
+    /// Whether playing the empty cell `m` would win the game for `player`.
+    pub fn completes_line(&self, m: i16, player: Player) -> bool {
+        if self.variant == Variant::Exact {
+            return self.exact_line_through(m, player);
+        }
+
+        let (p, q) = (player.index(), player.next().index());
+        self.lines.windows_of(m).iter().any(|&w| {
+            let counts = self.window_counts[w as usize];
+            counts[p] as i8 == self.k - 1 && counts[q] == 0
+        })
+    }
+
+    /// Whether `m`, taken as `player`'s stone, sits in a line of exactly k.
+    fn exact_line_through(&self, m: i16, player: Player) -> bool {
+        [(1, 0), (0, 1), (1, 1), (1, -1)].iter().any(|&(dx, dy)| {
+            let length =
+                1 + self.run_length(m, player, dx, dy) + self.run_length(m, player, -dx, -dy);
+            length == self.k as i16
+        })
+    }
+
+    /// How many of `player`'s stones follow `m` in direction `(dx, dy)`.
+    fn run_length(&self, m: i16, player: Player, dx: i16, dy: i16) -> i16 {
+        let (mut x, mut y) = (m % self.n + dx, m / self.n + dy);
+        let mut length = 0;
+        while x >= 0 && y >= 0 && x < self.n && y < self.n {
+            if self.cells[(y * self.n + x) as usize] != Some(player) {
+                break;
+            }
+            length += 1;
+            x += dx;
+            y += dy;
+        }
+
+        length
     }
 }
 
-#[allow(dead_code)]
-pub fn from_board_string_to_state(board: &str) -> (usize, Vec<Option<Player>>) {
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+            && self.n == other.n
+            && self.k == other.k
+            && self.variant == other.variant
+            && self.to_move == other.to_move
+            && self.cells == other.cells
+    }
+}
+
+impl Eq for Board {}
+
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Board")
+            .field("n", &self.n)
+            .field("k", &self.k)
+            .field("variant", &self.variant)
+            .field("to_move", &self.to_move)
+            .field("cells", &to_cell_string(&self.cells))
+            .finish()
+    }
+}
+
+/// The plain `[X][ ][O]` board format, as read by `from_board_string_to_state`.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = vec![];
+        Renderer::plain()
+            .render(self, &mut buffer)
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&buffer))
+    }
+}
+
+impl GameBoard for Board {
+    fn n(&self) -> i16 {
+        self.n
+    }
+
+    fn k(&self) -> i8 {
+        self.k
+    }
+
+    fn cell(&self, m: i16) -> Option<Player> {
+        self.cells[m as usize]
+    }
+
+    fn legal_moves(&self) -> Vec<i16> {
+        (0..self.cells.len() as i16)
+            .filter(|&m| self.cells[m as usize].is_none())
+            .collect()
+    }
+
+    fn is_tie(&self) -> bool {
+        Board::is_tie(self)
+    }
+
+    fn apply_move(&mut self, m: i16, player: Player) -> Result<Option<Player>, Box<dyn Error>> {
+        Board::apply_move(self, m, player)
+    }
+
+    fn undo_last_move(&mut self) -> Result<(), Box<dyn Error>> {
+        Board::undo_last_move(self)
+    }
+
+    fn check_winner_from(&self, m: i16) -> Option<Player> {
+        Board::check_winner_from(self, m)
+    }
+}
+
+/// Reads the plain `[X][ ][O]` format: one row per line, as many rows as
+/// there are cells in each.
+pub fn from_board_string_to_state(board: &str) -> Result<(usize, Vec<Option<Player>>), StateError> {
     let mut cells = vec![];
-    let mut prev_c = ' ';
-    let mut board_size = 0;
-    for c in board.trim().chars() {
-        if board_size == 0 && !cells.is_empty() && c == '\n' {
-            board_size = cells.len();
+    let mut row_lengths = vec![];
+    for line in board.trim().lines() {
+        let row_start = cells.len();
+        let mut prev_c = ' ';
+        for c in line.chars() {
+            if c == ' ' && prev_c == '[' {
+                cells.push(None);
+            } else if c == 'X' {
+                cells.push(Some(Player::X));
+            } else if c == 'O' {
+                cells.push(Some(Player::O));
+            } else if !['[', ']', ' '].contains(&c) {
+                return Err(StateError::InvalidCell);
+            }
+            prev_c = c;
         }
 
-        if c == ' ' && prev_c == '[' {
-            cells.push(None);
-        } else if c == 'X' {
-            cells.push(Some(Player::X));
-        } else if c == 'O' {
-            cells.push(Some(Player::O));
-        } else if !['[', ']', ' ', '\n'].contains(&c) {
-            println!("character '{c}' invalid");
-            panic!("invalid character encountered")
+        if cells.len() > row_start {
+            row_lengths.push(cells.len() - row_start);
         }
-        prev_c = c;
     }
 
-    if cells.len() != board_size * board_size {
-        panic!("boardsize inconsistent")
+    let n = row_lengths.len();
+    check_square(cells.len(), n)?;
+    if row_lengths.iter().any(|&length| length != n) {
+        return Err(StateError::NotSquare);
     }
 
-    (board_size, cells)
+    Ok((n, cells))
 }
 
-#[allow(dead_code)]
-pub fn from_cell_string_to_state(cell_string: &str) -> (usize, Vec<Option<Player>>) {
-    let cells: Vec<Option<Player>> = cell_string
+/// Whether `cells` make up a board of `n` by `n` that isn't too large.
+fn check_square(cells: usize, n: usize) -> Result<(), StateError> {
+    if n > MAX_N {
+        return Err(StateError::TooLarge);
+    }
+    if n == 0 || cells != n * n {
+        return Err(StateError::NotSquare);
+    }
+
+    Ok(())
+}
+
+pub fn to_board_string(cells: &[Option<Player>], n: usize) -> String {
+    let mut board = String::new();
+    for row in cells.chunks(n) {
+        for cell in row {
+            board.push('[');
+            board.push(match cell {
+                Some(Player::X) => 'X',
+                Some(Player::O) => 'O',
+                None => ' ',
+            });
+            board.push(']');
+        }
+        board.push('\n');
+    }
+
+    board
+}
+
+pub fn to_cell_string(cells: &[Option<Player>]) -> String {
+    cells
+        .iter()
+        .map(|c| match c {
+            Some(Player::X) => 'X',
+            Some(Player::O) => 'O',
+            None => '_',
+        })
+        .collect()
+}
+
+/// Reads the `<cells>` format: `X`, `O` and `_`, row by row.
+pub fn from_cell_string_to_state(
+    cell_string: &str,
+) -> Result<(usize, Vec<Option<Player>>), StateError> {
+    let cells = cell_string
         .trim()
         .chars()
         .map(|c| match c {
-            'X' => Some(Player::X),
-            'O' => Some(Player::O),
-            '_' => None,
-            _ => {
-                println!("character '{c}' invalid");
-                panic!("invalid character encountered")
-            }
+            'X' => Ok(Some(Player::X)),
+            'O' => Ok(Some(Player::O)),
+            '_' => Ok(None),
+            _ => Err(StateError::InvalidCell),
         })
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let board_size = (cells.len() as f64).sqrt();
-    if board_size.fract() != 0.0 {
-        panic!("boardsize inconsistent")
-    }
+    let n = (cells.len() as f64).sqrt() as usize;
+    check_square(cells.len(), n)?;
 
-    (board_size as usize, cells)
+    Ok((n, cells))
 }
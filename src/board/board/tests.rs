@@ -0,0 +1,177 @@
+use crate::board;
+use crate::board::{Board, Player, Variant};
+
+mod tests {
+    use super::*;
+
+    fn board_from_string(board: &str, k: i8) -> Board {
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
+        Board::new_from_state(n as i16, k, cells)
+    }
+
+    #[test]
+    fn threat_counts() {
+        let test_board = board_from_string(
+            "
+        [X][X][ ][ ]
+        [ ][O][ ][ ]
+        [ ][ ][ ][ ]
+        [ ][ ][ ][ ]
+        ",
+            3,
+        );
+
+        assert_eq!(test_board.threat_count(Player::X, 2), 1);
+        assert_eq!(test_board.threat_count(Player::O, 2), 0);
+        assert!(test_board.has_winning_move(Player::X));
+        assert!(!test_board.has_winning_move(Player::O));
+        assert_eq!(test_board.winning_moves(Player::X), [2]);
+        assert!(test_board.completes_line(2, Player::X));
+        assert!(!test_board.completes_line(3, Player::X));
+    }
+
+    #[test]
+    fn undo_restores_counts() {
+        let mut test_board = board_from_string(
+            "
+        [ ][X][ ][ ]
+        [ ][O][O][ ]
+        [ ][ ][X][ ]
+        [ ][ ][ ][ ]
+        ",
+            3,
+        );
+        let before: Vec<u32> = (0..=3)
+            .flat_map(|c| {
+                [
+                    test_board.threat_count(Player::X, c),
+                    test_board.threat_count(Player::O, c),
+                ]
+            })
+            .collect();
+
+        for m in test_board.winning_moves(Player::O) {
+            let winner = test_board
+                .apply_move(m, Player::O)
+                .expect("move should be valid");
+            assert_eq!(winner, Some(Player::O));
+            test_board.undo_last_move().expect("undo should be valid");
+        }
+
+        let after: Vec<u32> = (0..=3)
+            .flat_map(|c| {
+                [
+                    test_board.threat_count(Player::X, c),
+                    test_board.threat_count(Player::O, c),
+                ]
+            })
+            .collect();
+        assert_eq!(before, after);
+        assert_eq!(test_board.winning_moves(Player::O), [4, 7]);
+    }
+
+    #[test]
+    fn check_winner_from() {
+        let test_board = board_from_string(
+            "
+        [X][ ][ ]
+        [ ][X][ ]
+        [O][O][X]
+        ",
+            3,
+        );
+
+        assert_eq!(test_board.check_winner_from(4), Some(Player::X));
+        assert_eq!(test_board.check_winner_from(6), None);
+        assert_eq!(test_board.check_winner_from(1), None);
+    }
+
+    #[test]
+    fn zobrist_matches_loaded_state() {
+        let loaded = board_from_string(
+            "
+        [X][ ][ ]
+        [ ][O][ ]
+        [ ][ ][X]
+        ",
+            3,
+        );
+
+        let mut played = Board::new(3, 3);
+        for (m, p) in [(8, Player::X), (4, Player::O), (0, Player::X)] {
+            played.apply_move(m, p).expect("move should be valid");
+        }
+
+        assert_eq!(loaded.to_move(), Player::O);
+        assert_eq!(played.zobrist(), loaded.zobrist());
+        assert_eq!(played, loaded);
+    }
+
+    #[test]
+    fn zobrist_undo_and_side_to_move() {
+        let mut test_board = Board::new(3, 3);
+        let empty = test_board.zobrist();
+
+        test_board
+            .apply_move(4, Player::X)
+            .expect("move should be valid");
+        assert_ne!(test_board.zobrist(), empty);
+
+        test_board.undo_last_move().expect("undo should be valid");
+        assert_eq!(test_board.zobrist(), empty);
+
+        test_board.set_to_move(Player::O);
+        assert_ne!(test_board.zobrist(), empty);
+        assert_ne!(test_board, Board::new(3, 3));
+        assert_ne!(Board::new(3, 3).zobrist(), Board::new(4, 3).zobrist());
+    }
+
+    #[test]
+    fn exact_variant_ignores_overlines() {
+        let mut test_board = board_from_string(
+            "
+        [X][X][ ][X][ ]
+        [ ][ ][ ][ ][ ]
+        [O][O][ ][ ][ ]
+        [ ][ ][ ][ ][ ]
+        [ ][ ][ ][ ][ ]
+        ",
+            3,
+        )
+        .with_variant(Variant::Exact);
+
+        assert!(!test_board.completes_line(2, Player::X));
+        assert!(!test_board.completes_line(4, Player::X));
+        assert_eq!(test_board.winning_moves(Player::X), Vec::<i16>::new());
+        assert_eq!(test_board.winning_moves(Player::O), [12]);
+
+        let winner = test_board
+            .apply_move(2, Player::X)
+            .expect("move should be valid");
+        assert_eq!(winner, None);
+        assert!(test_board.winning_line().is_empty());
+
+        let winner = test_board
+            .apply_move(12, Player::O)
+            .expect("move should be valid");
+        assert_eq!(winner, Some(Player::O));
+        assert_eq!(test_board.winning_line(), [10, 11, 12]);
+    }
+
+    #[test]
+    fn malformed_states_are_errors() {
+        assert!(board::from_board_string_to_state("[X][ ]\n[ ][Y]").is_err());
+        assert!(board::from_board_string_to_state("[X][ ][ ]\n[ ][O]\n[ ][ ][ ][ ]").is_err());
+        assert!(board::from_board_string_to_state("").is_err());
+        assert_eq!(
+            board::from_board_string_to_state("[X][ ]\n[ ][O]")
+                .expect("board should parse")
+                .0,
+            2
+        );
+
+        assert!(board::from_cell_string_to_state("X_O_Y____").is_err());
+        assert!(board::from_cell_string_to_state("X_O__").is_err());
+        assert!(board::from_cell_string_to_state(&"_".repeat(182 * 182)).is_err());
+    }
+}
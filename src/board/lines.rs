@@ -0,0 +1,88 @@
+/// Every k-length window on an n x n board, precomputed once and shared
+/// between clones of the same board.
+pub struct Lines {
+    k: usize,
+    window_cells: Vec<i16>,
+    window_dirs: Vec<u8>,
+    cell_offsets: Vec<u32>,
+    cell_windows: Vec<u32>,
+}
+
+struct Dir {
+    dx: i16,
+    dy: i16,
+}
+
+const DIRECTIONS: [Dir; 4] = [
+    Dir { dx: 1, dy: 0 },
+    Dir { dx: 0, dy: 1 },
+    Dir { dx: 1, dy: 1 },
+    Dir { dx: 1, dy: -1 },
+];
+
+impl Lines {
+    pub fn new(n: i16, k: i8) -> Self {
+        let k = k.max(1) as i16;
+        let mut window_cells: Vec<i16> = vec![];
+        let mut window_dirs: Vec<u8> = vec![];
+        let mut per_cell: Vec<Vec<u32>> = vec![vec![]; (n * n) as usize];
+
+        for (dir, d) in DIRECTIONS.iter().enumerate() {
+            for y in 0..n {
+                for x in 0..n {
+                    let end_x = x + d.dx * (k - 1);
+                    let end_y = y + d.dy * (k - 1);
+                    if end_x < 0 || end_y < 0 || end_x >= n || end_y >= n {
+                        continue;
+                    }
+
+                    let w = (window_cells.len() / k as usize) as u32;
+                    window_dirs.push(dir as u8);
+                    for i in 0..k {
+                        let m = (y + d.dy * i) * n + x + d.dx * i;
+                        window_cells.push(m);
+                        per_cell[m as usize].push(w);
+                    }
+                }
+            }
+        }
+
+        let mut cell_offsets = Vec::with_capacity(per_cell.len() + 1);
+        let mut cell_windows = vec![];
+        cell_offsets.push(0);
+        for windows in per_cell {
+            cell_windows.extend(windows);
+            cell_offsets.push(cell_windows.len() as u32);
+        }
+
+        Self {
+            k: k as usize,
+            window_cells,
+            window_dirs,
+            cell_offsets,
+            cell_windows,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.window_cells.len() / self.k
+    }
+
+    /// The cells making up window `w`, in order along its direction.
+    pub fn cells(&self, w: usize) -> &[i16] {
+        &self.window_cells[w * self.k..(w + 1) * self.k]
+    }
+
+    /// The `(dx, dy)` step from one cell of window `w` to the next.
+    pub fn direction(&self, w: usize) -> (i16, i16) {
+        let d = &DIRECTIONS[self.window_dirs[w] as usize];
+        (d.dx, d.dy)
+    }
+
+    /// The windows that contain cell `m`.
+    pub fn windows_of(&self, m: i16) -> &[u32] {
+        let start = self.cell_offsets[m as usize] as usize;
+        let end = self.cell_offsets[m as usize + 1] as usize;
+        &self.cell_windows[start..end]
+    }
+}
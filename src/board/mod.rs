@@ -1,8 +1,19 @@
+mod bitboard;
 mod board;
+mod lines;
+pub mod notation;
 mod player;
+pub mod render;
+pub mod symmetry;
+mod zobrist;
 
-pub use board::Board;
+pub use bitboard::{BitBoard, U256};
+pub use board::{Board, GameBoard, MAX_N, Variant, check_rules};
+pub use notation::Notation;
 pub use player::Player;
+pub use render::Renderer;
 
 pub use board::from_board_string_to_state;
 pub use board::from_cell_string_to_state;
+pub use board::to_board_string;
+pub use board::to_cell_string;
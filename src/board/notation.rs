@@ -0,0 +1,104 @@
+use std::error::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// How moves are written for humans and files: gomoku-style coordinates
+/// (`h8`, column letters from the left and row numbers from the bottom) or the
+/// raw cell index used internally.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Notation {
+    Coord,
+    Index,
+}
+
+#[derive(strum_macros::Display, Debug)]
+pub enum NotationError {
+    MalformedMove,
+    MoveOutOfRange,
+}
+
+impl Error for NotationError {}
+
+impl Notation {
+    pub fn format(self, m: i16, n: i16) -> String {
+        match self {
+            Notation::Coord => to_coord(m, n),
+            Notation::Index => m.to_string(),
+        }
+    }
+
+    /// Parses a move written in this notation.
+    pub fn parse(self, s: &str, n: i16) -> Result<i16, NotationError> {
+        match self {
+            Notation::Coord => from_coord(s, n),
+            Notation::Index => from_index(s, n),
+        }
+    }
+}
+
+pub fn to_coord(m: i16, n: i16) -> String {
+    let (x, y) = (m % n, m / n);
+
+    let mut column = vec![];
+    let mut c = x + 1;
+    while c > 0 {
+        c -= 1;
+        column.push((b'a' + (c % 26) as u8) as char);
+        c /= 26;
+    }
+
+    column.iter().rev().collect::<String>() + &(n - y).to_string()
+}
+
+pub fn from_coord(s: &str, n: i16) -> Result<i16, NotationError> {
+    let s = s.trim().to_ascii_lowercase();
+    let split = s
+        .find(|c: char| !c.is_ascii_lowercase())
+        .ok_or(NotationError::MalformedMove)?;
+    let (letters, digits) = s.split_at(split);
+    if letters.is_empty() || digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(NotationError::MalformedMove);
+    }
+
+    let mut x: i32 = 0;
+    for c in letters.bytes() {
+        x = x * 26 + (c - b'a') as i32 + 1;
+        if x > n as i32 {
+            return Err(NotationError::MoveOutOfRange);
+        }
+    }
+    let x = x - 1;
+
+    let row: i32 = digits.parse().map_err(|_| NotationError::MoveOutOfRange)?;
+    if row < 1 || row > n as i32 {
+        return Err(NotationError::MoveOutOfRange);
+    }
+
+    Ok(((n as i32 - row) * n as i32 + x) as i16)
+}
+
+pub fn from_index(s: &str, n: i16) -> Result<i16, NotationError> {
+    let s = s.trim();
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        return Err(NotationError::MalformedMove);
+    }
+
+    let m: i32 = s.parse().map_err(|_| NotationError::MoveOutOfRange)?;
+    if m >= (n as i32) * (n as i32) {
+        return Err(NotationError::MoveOutOfRange);
+    }
+
+    Ok(m as i16)
+}
+
+/// Accepts both coordinates and raw indices, so `h8` and `112` are the same
+/// move on a 15x15 board. Used for state files, which may hold either.
+pub fn parse_move(s: &str, n: i16) -> Result<i16, NotationError> {
+    let s = s.trim();
+    if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+        return from_index(s, n);
+    }
+
+    from_coord(s, n)
+}
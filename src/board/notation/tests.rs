@@ -0,0 +1,54 @@
+use crate::board::notation::{self, Notation};
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coords_round_trip() {
+        assert_eq!(notation::to_coord(0, 3), "a3");
+        assert_eq!(notation::to_coord(8, 3), "c1");
+        assert_eq!(notation::to_coord(112, 15), "h8");
+        assert_eq!(notation::to_coord(26, 30), "aa30");
+
+        for n in [3, 15, 30] {
+            for m in 0..n * n {
+                let coord = notation::to_coord(m, n);
+                assert_eq!(
+                    notation::from_coord(&coord, n).expect("coord should parse"),
+                    m
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_by_notation() {
+        assert_eq!(
+            Notation::Coord.parse("H8", 15).expect("move should parse"),
+            112
+        );
+        assert_eq!(
+            Notation::Index.parse("112", 15).expect("move should parse"),
+            112
+        );
+        assert!(Notation::Coord.parse("112", 15).is_err());
+        assert!(Notation::Index.parse("h8", 15).is_err());
+        assert_eq!(
+            notation::parse_move("112", 15).expect("move should parse"),
+            notation::parse_move("h8", 15).expect("move should parse")
+        );
+        assert_eq!(Notation::Index.format(112, 15), "112");
+        assert_eq!(Notation::Coord.format(112, 15), "h8");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(notation::parse_move("d1", 3).is_err());
+        assert!(notation::parse_move("a4", 3).is_err());
+        assert!(notation::parse_move("a0", 3).is_err());
+        assert!(notation::parse_move("9", 3).is_err());
+        assert!(notation::parse_move("a", 3).is_err());
+        assert!(notation::parse_move("1a", 3).is_err());
+        assert!(notation::parse_move("", 3).is_err());
+    }
+}
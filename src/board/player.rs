@@ -1,4 +1,13 @@
-#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, strum_macros::Display)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    clap::ValueEnum,
+    strum_macros::Display,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub enum Player {
     X,
     O,
@@ -12,6 +21,13 @@ impl Player {
         }
     }
 
+    pub fn index(self) -> usize {
+        match self {
+            Player::X => 0,
+            Player::O => 1,
+        }
+    }
+
     pub fn next(self) -> Self {
         match self {
             Player::X => Player::O,
@@ -0,0 +1,192 @@
+use std::io::{self, IsTerminal, Write};
+
+use crate::board::{Board, Player, notation};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum GridStyle {
+    /// `[X][ ][O]` cells, the same format as `<board>` state files
+    Brackets,
+    /// Box-drawing grid lines around every cell
+    Unicode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Color only when stdout is a terminal
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => io::stdout().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Draws a board to any writer. The last move is wrapped in `( )` and a
+/// completed line is shown inverted, or wrapped in `{ }` without color.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    style: GridStyle,
+    coords: bool,
+    color: bool,
+    markers: bool,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer::new()
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            style: GridStyle::Brackets,
+            coords: false,
+            color: ColorMode::Auto.enabled(),
+            markers: true,
+        }
+    }
+
+    /// No color, labels or markers, so the output parses back as a board.
+    pub fn plain() -> Self {
+        Self {
+            style: GridStyle::Brackets,
+            coords: false,
+            color: false,
+            markers: false,
+        }
+    }
+
+    pub fn style(mut self, style: GridStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn coords(mut self, coords: bool) -> Self {
+        self.coords = coords;
+        self
+    }
+
+    pub fn color(mut self, color: ColorMode) -> Self {
+        self.color = color.enabled();
+        self
+    }
+
+    pub fn render<W: Write>(&self, board: &Board, w: &mut W) -> io::Result<()> {
+        let n = board.n();
+        let label_width = n.to_string().len();
+        let winning_line = if self.markers {
+            board.winning_line()
+        } else {
+            vec![]
+        };
+        let last_move = if self.markers {
+            board.last_move()
+        } else {
+            None
+        };
+        let margin = if self.coords {
+            " ".repeat(label_width + 1)
+        } else {
+            String::new()
+        };
+
+        if self.style == GridStyle::Unicode {
+            self.rule(w, &margin, n, ['┌', '┬', '┐'])?;
+        }
+
+        for y in 0..n {
+            if self.coords {
+                write!(w, "{:>label_width$} ", n - y)?;
+            }
+            if self.style == GridStyle::Unicode {
+                write!(w, "│")?;
+            }
+
+            for x in 0..n {
+                let m = y * n + x;
+                let cell = board.cells()[m as usize];
+                self.cell(w, cell, last_move == Some(m), winning_line.contains(&m))?;
+            }
+            writeln!(w)?;
+
+            if self.style == GridStyle::Unicode {
+                if y + 1 < n {
+                    self.rule(w, &margin, n, ['├', '┼', '┤'])?;
+                } else {
+                    self.rule(w, &margin, n, ['└', '┴', '┘'])?;
+                }
+            }
+        }
+
+        if self.coords {
+            let cell_width = match self.style {
+                GridStyle::Brackets => 3,
+                GridStyle::Unicode => 4,
+            };
+            write!(w, "{margin}")?;
+            if self.style == GridStyle::Unicode {
+                write!(w, " ")?;
+            }
+            for x in 0..n {
+                let column = notation::to_coord(x, n);
+                let column = column.trim_end_matches(|c: char| c.is_ascii_digit());
+                write!(w, "{column:^cell_width$}")?;
+            }
+            writeln!(w)?;
+        }
+
+        Ok(())
+    }
+
+    fn cell<W: Write>(
+        &self,
+        w: &mut W,
+        cell: Option<Player>,
+        last: bool,
+        winning: bool,
+    ) -> io::Result<()> {
+        let (open, close) = match (self.style, last, winning && !self.color) {
+            (_, _, true) => ('{', '}'),
+            (_, true, _) => ('(', ')'),
+            (GridStyle::Brackets, _, _) => ('[', ']'),
+            (GridStyle::Unicode, _, _) => (' ', ' '),
+        };
+
+        let stone = match cell {
+            Some(p) if self.color && winning => format!("\x1b[1;7m{}", p.pretty()),
+            Some(p) if self.color => p.pretty(),
+            Some(p) => p.to_string(),
+            None => " ".to_string(),
+        };
+
+        write!(w, "{open}{stone}{close}")?;
+        if self.style == GridStyle::Unicode {
+            write!(w, "│")?;
+        }
+
+        Ok(())
+    }
+
+    fn rule<W: Write>(
+        &self,
+        w: &mut W,
+        margin: &str,
+        n: i16,
+        corners: [char; 3],
+    ) -> io::Result<()> {
+        let [left, middle, right] = corners;
+        let cells = vec!["───"; n as usize].join(&middle.to_string());
+        writeln!(w, "{margin}{left}{cells}{right}")
+    }
+}
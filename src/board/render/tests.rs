@@ -0,0 +1,74 @@
+use crate::board;
+use crate::board::render::{ColorMode, GridStyle};
+use crate::board::{Board, Player, Renderer};
+
+mod tests {
+    use super::*;
+
+    fn render(renderer: Renderer, board: &Board) -> String {
+        let mut buffer = vec![];
+        renderer
+            .render(board, &mut buffer)
+            .expect("render should succeed");
+        String::from_utf8(buffer).expect("render should be utf-8")
+    }
+
+    fn test_board() -> Board {
+        let mut test_board = Board::new(3, 3);
+        for (m, p) in [(0, Player::X), (4, Player::O), (1, Player::X)] {
+            test_board.apply_move(m, p).expect("move should be valid");
+        }
+        test_board
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let test_board = test_board();
+        let shown = test_board.to_string();
+
+        assert_eq!(shown, "[X][X][ ]\n[ ][O][ ]\n[ ][ ][ ]\n");
+        let (n, cells) = board::from_board_string_to_state(&shown).expect("board should parse");
+        assert_eq!(n, 3);
+        assert_eq!(cells, test_board.cells());
+    }
+
+    #[test]
+    fn coords_and_last_move() {
+        let renderer = Renderer::new().color(ColorMode::Never).coords(true);
+
+        assert_eq!(
+            render(renderer, &test_board()),
+            "3 [X](X)[ ]\n2 [ ][O][ ]\n1 [ ][ ][ ]\n   a  b  c \n"
+        );
+    }
+
+    #[test]
+    fn winning_line_without_color() {
+        let mut test_board = test_board();
+        test_board
+            .apply_move(2, Player::X)
+            .expect("move should be valid");
+
+        let shown = render(Renderer::new().color(ColorMode::Never), &test_board);
+        assert_eq!(shown, "{X}{X}{X}\n[ ][O][ ]\n[ ][ ][ ]\n");
+    }
+
+    #[test]
+    fn unicode_grid() {
+        let renderer = Renderer::plain().style(GridStyle::Unicode);
+        let shown = render(renderer, &Board::new(2, 2));
+
+        assert_eq!(
+            shown,
+            "┌───┬───┐\n│   │   │\n├───┼───┤\n│   │   │\n└───┴───┘\n"
+        );
+    }
+
+    #[test]
+    fn color_codes() {
+        let shown = render(Renderer::plain().color(ColorMode::Always), &test_board());
+
+        assert!(shown.contains(&Player::X.pretty()));
+        assert!(!render(Renderer::plain(), &test_board()).contains('\x1b'));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::board::{Board, Player};
+
+#[cfg(test)]
+mod tests;
+
+/// The 8 dihedral symmetries of a square board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Where cell `m` of an n x n board ends up under this symmetry.
+    pub fn apply(self, m: i16, n: i16) -> i16 {
+        let (x, y) = (m % n, m / n);
+        let last = n - 1;
+        let (nx, ny) = match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (last - y, x),
+            Symmetry::Rotate180 => (last - x, last - y),
+            Symmetry::Rotate270 => (y, last - x),
+            Symmetry::FlipHorizontal => (last - x, y),
+            Symmetry::FlipVertical => (x, last - y),
+            Symmetry::Transpose => (y, x),
+            Symmetry::AntiTranspose => (last - y, last - x),
+        };
+
+        ny * n + nx
+    }
+
+    #[allow(dead_code)]
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            s => s,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn apply_moves(self, moves: &[i16], n: i16) -> Vec<i16> {
+        moves.iter().map(|&m| self.apply(m, n)).collect()
+    }
+}
+
+#[allow(dead_code)]
+fn cell_key(cell: Option<Player>) -> u8 {
+    match cell {
+        Some(Player::X) => 0,
+        Some(Player::O) => 1,
+        None => 2,
+    }
+}
+
+/// The symmetry taking `board` to its canonical form: the transformed board
+/// whose cells compare smallest, with ties going to the earlier symmetry.
+#[allow(dead_code)]
+pub fn canonical_symmetry(board: &Board) -> Symmetry {
+    let n = board.n();
+    let cells = board.cells();
+    let mut best = Symmetry::Identity;
+
+    for &s in &Symmetry::ALL[1..] {
+        let inverse = s.inverse();
+        let inv_best = best.inverse();
+        for m in 0..cells.len() as i16 {
+            let candidate = cell_key(cells[inverse.apply(m, n) as usize]);
+            let current = cell_key(cells[inv_best.apply(m, n) as usize]);
+            if candidate != current {
+                if candidate < current {
+                    best = s;
+                }
+                break;
+            }
+        }
+    }
+
+    best
+}
+
+/// The canonical form of `board` together with the symmetry that produced it.
+#[allow(dead_code)]
+pub fn canonical(board: &Board) -> (Board, Symmetry) {
+    let s = canonical_symmetry(board);
+    (board.transformed(s), s)
+}
+
+/// The symmetries that leave the position unchanged, identity included.
+pub fn stabilizer(board: &Board) -> Vec<Symmetry> {
+    let n = board.n();
+    let cells = board.cells();
+
+    Symmetry::ALL
+        .into_iter()
+        .filter(|&s| {
+            (0..cells.len() as i16).all(|m| cells[s.apply(m, n) as usize] == cells[m as usize])
+        })
+        .collect()
+}
+
+/// `moves` without the ones equivalent to an earlier move under a symmetry of
+/// the position, so e.g. the empty 3x3 board only keeps a corner, an edge and
+/// the centre.
+pub fn unique_moves(board: &Board, moves: &[i16]) -> Vec<i16> {
+    let symmetries = stabilizer(board);
+    if symmetries.len() == 1 {
+        return moves.to_vec();
+    }
+
+    let n = board.n();
+    let mut unique: Vec<i16> = Vec::with_capacity(moves.len());
+    for &m in moves {
+        if !symmetries.iter().any(|s| unique.contains(&s.apply(m, n))) {
+            unique.push(m);
+        }
+    }
+
+    unique
+}
@@ -0,0 +1,63 @@
+use crate::board;
+use crate::board::symmetry::{self, Symmetry};
+use crate::board::{Board, Player};
+
+mod tests {
+    use super::*;
+
+    fn board_from_string(board: &str) -> Board {
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
+        Board::new_from_state(n as i16, 3, cells)
+    }
+
+    #[test]
+    fn apply_and_inverse() {
+        for s in Symmetry::ALL {
+            for m in 0..16 {
+                assert_eq!(s.inverse().apply(s.apply(m, 4), 4), m);
+            }
+        }
+
+        assert_eq!(Symmetry::Rotate90.apply(0, 3), 2);
+        assert_eq!(Symmetry::Transpose.apply(1, 3), 3);
+        assert_eq!(Symmetry::Rotate180.apply_moves(&[0, 4, 5], 3), [8, 4, 3]);
+    }
+
+    #[test]
+    fn canonical_is_shared_by_symmetric_boards() {
+        let test_board = board_from_string(
+            "
+        [ ][X][ ]
+        [ ][O][ ]
+        [ ][ ][X]
+        ",
+        );
+        let (canonical, _) = symmetry::canonical(&test_board);
+
+        for s in Symmetry::ALL {
+            let transformed = test_board.transformed(s);
+            let (other, found) = symmetry::canonical(&transformed);
+            assert_eq!(canonical, other);
+            assert_eq!(transformed.transformed(found), canonical);
+        }
+    }
+
+    #[test]
+    fn unique_moves_empty_board() {
+        let test_board = Board::new(3, 3);
+        let moves: Vec<i16> = (0..9).collect();
+
+        assert_eq!(symmetry::unique_moves(&test_board, &moves), [0, 1, 4]);
+    }
+
+    #[test]
+    fn unique_moves_mirrored_board() {
+        let mut test_board = Board::new(3, 3);
+        test_board
+            .apply_move(1, Player::X)
+            .expect("move should be valid");
+        let moves: Vec<i16> = (0..9).filter(|&m| m != 1).collect();
+
+        assert_eq!(symmetry::unique_moves(&test_board, &moves), [0, 3, 4, 6, 7]);
+    }
+}
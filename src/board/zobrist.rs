@@ -0,0 +1,24 @@
+use crate::board::Player;
+
+/// Key toggled into the hash whenever O is the side to move.
+pub const SIDE_KEY: u64 = 0x9e6c_63d0_676a_9a99;
+
+/// Key toggled into the hash for boards played under the exact-k variant.
+pub const EXACT_KEY: u64 = 0x5851_f42d_4c95_7f2d;
+
+/// Keys are derived from the cell index rather than drawn from a table, so
+/// every board of the same size hashes identically across runs and processes.
+pub fn cell_key(m: i16, player: Player) -> u64 {
+    splitmix64((((m as u64) << 1) | player.index() as u64) + 1)
+}
+
+pub fn rules_key(n: i16, k: i8) -> u64 {
+    splitmix64((((n as u64) << 8) | k as u8 as u64) ^ 0xa076_1d64_78bd_642f)
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -1,43 +1,462 @@
-use clap::Parser;
+use std::time::Duration;
 
-use crate::{board::Player, filestate::FileState};
+use clap::{Parser, Subcommand};
+
+use crate::{
+    board::{
+        Notation, Player, Variant,
+        render::{ColorMode, GridStyle},
+    },
+    config::Profile,
+    filestate::FileState,
+    mct_bot::{Clock, Difficulty, RolloutPolicy, TreeFormat},
+    report::Format,
+};
+
+#[cfg(test)]
+mod tests;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(
+    author,
+    version,
+    about = "Play k-in-a-row against a Monte Carlo tree search bot"
+)]
 pub struct Args {
-    /// The next player to make a move
-    #[arg(short, long, value_enum, value_name = "PLAYER", default_value_t = Player::X)]
-    pub player: Player,
+    #[command(subcommand)]
+    pub command: Option<Command>,
 
-    /// The board will be n x n size
-    #[arg(short, value_name = "SIZE", default_value_t = 3)]
-    pub n: i16,
+    /// How results are written to stdout
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = Format::Text, global = true)]
+    pub format: Format,
 
-    /// Will output only the next move, otherwise plays a full game
-    #[arg(long, value_name = "NEXT_MOVE", default_value_t = false)]
-    pub next_move: bool,
+    /// Log more to stderr: -v for search summaries, -vv for a full trace
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Log less to stderr: -q for errors only, -qq for nothing
+    #[arg(short, long, action = clap::ArgAction::Count, global = true, conflicts_with = "verbose")]
+    pub quiet: u8,
+
+    #[command(flatten)]
+    pub rules: RulesArgs,
+
+    #[command(flatten)]
+    pub bot: BotArgs,
+
+    #[command(flatten)]
+    pub position: PositionArgs,
+
+    #[command(flatten)]
+    pub display: DisplayArgs,
+
+    #[command(flatten)]
+    pub legacy: LegacyArgs,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Play against the bot in a full-screen terminal UI
+    Play {
+        /// Let the bot play both sides
+        #[arg(long, default_value_t = false)]
+        watch: bool,
+    },
+
+    /// Find the next move for the loaded position
+    Move {
+        /// The state file to save, otherwise the move is printed
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output_file: Option<String>,
+
+        /// How to store state
+        #[arg(long, value_enum, value_name = "STATE_OUTPUT_TYPE", default_value_t = FileState::Board)]
+        output_type: FileState,
+    },
+
+    /// Search the loaded position and list the best candidate moves
+    Analyze {
+        /// How many candidates to list
+        #[arg(long, value_name = "COUNT", default_value_t = 5)]
+        top: usize,
+
+        /// Write the search tree to this file, for debugging
+        #[arg(long, value_name = "TREE_FILE")]
+        dump_tree: Option<String>,
+
+        /// How to write the search tree
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            default_value_t = TreeFormat::Dot,
+            requires = "dump_tree"
+        )]
+        tree_format: TreeFormat,
+
+        /// How many moves below the root to dump
+        #[arg(
+            long,
+            value_name = "PLIES",
+            default_value_t = 3,
+            requires = "dump_tree"
+        )]
+        tree_depth: usize,
+
+        /// Leave out nodes with fewer visits than this
+        #[arg(
+            long,
+            value_name = "VISITS",
+            default_value_t = 10,
+            requires = "dump_tree"
+        )]
+        tree_min_visits: i32,
+    },
+
+    /// Solve the loaded position exactly, for small boards
+    Solve {
+        /// Give up after searching this many positions
+        #[arg(long, value_name = "NODES", default_value_t = 5_000_000)]
+        node_limit: u64,
+    },
+
+    /// Score the loaded position with a depth-limited alpha-beta search over
+    /// the pattern evaluator
+    Evaluate {
+        /// How many plies to search
+        #[arg(long, value_name = "PLIES", default_value_t = 4)]
+        depth: u32,
+    },
+
+    /// Write the loaded position in another state format
+    Convert {
+        /// The state file to save, otherwise the state is printed
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output_file: Option<String>,
+
+        /// How to store state
+        #[arg(long, value_enum, value_name = "STATE_OUTPUT_TYPE", default_value_t = FileState::Board)]
+        output_type: FileState,
+    },
+
+    /// Let the bot play a full game against itself
+    Selfplay {
+        /// Will not print the board for each move
+        #[arg(short, long, default_value_t = false)]
+        silent: bool,
+    },
+
+    /// Measure search speed on the loaded position
+    Bench {
+        /// How many searches to average over
+        #[arg(long, value_name = "RUNS", default_value_t = 3)]
+        runs: u32,
+    },
+
+    /// Serve the HTTP/JSON API
+    Serve {
+        /// The address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+
+    /// Speak the Gomocup protocol on stdin/stdout for tournament managers
+    Engine,
+}
+
+/// Rules and bot flags are optional so that a config file can fill in the
+/// ones left out.
+#[derive(clap::Args, Debug)]
+pub struct RulesArgs {
+    /// The board will be n x n size [default: 3]
+    #[arg(short, value_name = "SIZE", global = true)]
+    pub n: Option<i16>,
+
+    /// How many in a row to win [default: 3]
+    #[arg(short, value_name = "WIN_CONDITION", global = true)]
+    pub k: Option<i8>,
+
+    /// Whether lines longer than k also win [default: freestyle]
+    #[arg(long, value_enum, value_name = "VARIANT", global = true)]
+    pub variant: Option<Variant>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BotArgs {
+    /// TOML file with the rules and named bot profiles
+    #[arg(long, value_name = "CONFIG_FILE", global = true)]
+    pub config: Option<String>,
+
+    /// The bot profile to use from the config file [default: default]
+    #[arg(long, value_name = "PROFILE", global = true, requires = "config")]
+    pub profile: Option<String>,
+
+    /// A preset bot strength; the other bot flags are applied on top
+    #[arg(long, value_enum, value_name = "LEVEL", global = true)]
+    pub difficulty: Option<Difficulty>,
+
+    /// Let the bot search on the opponent's time in `play` and `engine`
+    #[arg(long, default_value_t = false, global = true)]
+    pub ponder: bool,
+
+    /// How many seconds the bot is allowed to think [default: 5]
+    #[arg(short, long, value_name = "THINKING_SECONDS", global = true)]
+    pub think_time: Option<f64>,
+
+    /// Play under time control with this many seconds for the whole game;
+    /// replaces the fixed thinking time
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds, global = true)]
+    pub clock: Option<Duration>,
+
+    /// Seconds added to the clock after every move
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = parse_seconds,
+        default_value = "0",
+        requires = "clock",
+        global = true
+    )]
+    pub increment: Duration,
+
+    /// The UCT exploration constant
+    #[arg(long, value_name = "C", global = true)]
+    pub exploration: Option<f32>,
+
+    /// Progressive widening: a node may have this times sqrt(visits) children
+    #[arg(long, value_name = "FACTOR", global = true)]
+    pub widening: Option<f32>,
 
+    /// How much being next to an existing stone adds to a move's prior, 0
+    /// to 1
+    #[arg(long, value_name = "WEIGHT", global = true)]
+    pub neighbour_chance: Option<f32>,
+
+    /// What a won playout is worth
+    #[arg(long, value_name = "VALUE", global = true)]
+    pub win_value: Option<f32>,
+
+    /// What a drawn playout is worth
+    #[arg(long, value_name = "VALUE", global = true)]
+    pub draw_value: Option<f32>,
+
+    /// How playouts choose their moves [default: tactical]
+    #[arg(long, value_enum, value_name = "POLICY", global = true)]
+    pub rollout: Option<RolloutPolicy>,
+
+    /// Blend all-moves-as-first statistics into the search (RAVE)
+    #[arg(long, overrides_with = "no_rave", global = true)]
+    pub rave: bool,
+
+    /// Turn RAVE off, even if the profile turns it on
+    #[arg(long, overrides_with = "rave", global = true)]
+    pub no_rave: bool,
+
+    /// Stop playouts after this many moves and score them with the pattern
+    /// evaluator
+    #[arg(long, value_name = "PLIES", global = true)]
+    pub rollout_cutoff: Option<usize>,
+
+    /// Let the pattern evaluator's move scores count towards the move priors
+    #[arg(long, overrides_with = "no_pattern_priors", global = true)]
+    pub pattern_priors: bool,
+
+    /// Leave pattern scores out of the move priors, even if the profile
+    /// adds them
+    #[arg(long, overrides_with = "pattern_priors", global = true)]
+    pub no_pattern_priors: bool,
+
+    /// How much move priors bias the search, 0 for plain UCT
+    #[arg(long, value_name = "WEIGHT", global = true)]
+    pub prior_weight: Option<f32>,
+
+    /// The most nodes the search tree may hold; the least visited are
+    /// dropped when it fills up [default: 1000000]
+    #[arg(long, value_name = "NODES", global = true)]
+    pub max_nodes: Option<usize>,
+
+    /// The most positions the threat search visits before giving up on
+    /// finding a forced win [default: 20000]
+    #[arg(long, value_name = "NODES", global = true)]
+    pub threat_nodes: Option<u64>,
+}
+
+/// A `--flag`/`--no-flag` pair: `None` when neither is given, so the
+/// profile decides.
+fn switch(on: bool, off: bool) -> Option<bool> {
+    match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    let seconds: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
+
+impl BotArgs {
+    /// The bot settings given as flags, to be laid over a config profile.
+    pub fn profile(&self) -> Profile {
+        Profile {
+            difficulty: self.difficulty,
+            think_time: self.think_time,
+            exploration: self.exploration,
+            widening: self.widening,
+            neighbour_chance: self.neighbour_chance,
+            win_value: self.win_value,
+            draw_value: self.draw_value,
+            rollout: self.rollout,
+            rave: switch(self.rave, self.no_rave),
+            rollout_cutoff: self.rollout_cutoff,
+            pattern_priors: switch(self.pattern_priors, self.no_pattern_priors),
+            prior_weight: self.prior_weight,
+            max_nodes: self.max_nodes,
+            threat_nodes: self.threat_nodes,
+        }
+    }
+
+    pub fn clock(&self) -> Option<Clock> {
+        self.clock.map(|total| Clock::new(total, self.increment))
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PositionArgs {
     /// The state file to load, will override n
-    #[arg(long, value_name = "STATE_FILE")]
+    #[arg(long, value_name = "STATE_FILE", global = true)]
     pub state_file: Option<String>,
 
-    /// How to store state
-    #[arg(long, value_enum, value_name = "STATE_OUTPUT_TYPE", default_value_t = FileState::Board)]
+    /// Moves to play on the loaded board before thinking, e.g. "h8,h9,i8"
+    #[arg(
+        short,
+        long,
+        value_name = "MOVES",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub moves: Vec<String>,
+
+    /// The next player to make a move [default: whoever is to move in the
+    /// loaded position, X without a subcommand]
+    #[arg(short, long, value_enum, value_name = "PLAYER", global = true)]
+    pub player: Option<Player>,
+
+    /// How moves are read and written [default: coord, index without a
+    /// subcommand]
+    #[arg(long, value_enum, value_name = "NOTATION", global = true)]
+    pub notation: Option<Notation>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DisplayArgs {
+    /// How boards are drawn
+    #[arg(long, value_enum, value_name = "GRID", default_value_t = GridStyle::Brackets, global = true)]
+    pub grid: GridStyle,
+
+    /// Label the board with column letters and row numbers
+    #[arg(long, default_value_t = false, global = true)]
+    pub coords: bool,
+
+    /// When to color the board
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorMode::Auto, global = true)]
+    pub color: ColorMode,
+}
+
+// The mode flags from before subcommands existed. They are only read when no
+// subcommand is given. A doc comment here would become the `--help` text.
+#[derive(clap::Args, Debug)]
+pub struct LegacyArgs {
+    /// Same as `move`
+    #[arg(long, default_value_t = false, hide = true)]
+    pub next_move: bool,
+
+    /// Same as `engine`
+    #[arg(long, default_value_t = false, hide = true)]
+    pub engine: bool,
+
+    /// Same as `serve --addr ADDR`
+    #[arg(long, value_name = "ADDR", hide = true)]
+    pub serve: Option<String>,
+
+    /// Same as `play`
+    #[arg(long, default_value_t = false, hide = true)]
+    pub tui: bool,
+
+    /// Same as `play --watch`
+    #[arg(long, default_value_t = false, requires = "tui", hide = true)]
+    pub watch: bool,
+
+    /// Same as `move --output-type`
+    #[arg(long, value_enum, value_name = "STATE_OUTPUT_TYPE", default_value_t = FileState::Board, hide = true)]
     pub output_type: FileState,
 
-    /// The state file to save
-    #[arg(short, long, value_name = "OUTPUT_FILE")]
+    /// Same as `move --output-file`
+    #[arg(short, long, value_name = "OUTPUT_FILE", hide = true)]
     pub output_file: Option<String>,
 
-    /// How many in a row to win
-    #[arg(short, value_name = "WIN_CONDITION", default_value_t = 3)]
-    pub k: i8,
+    /// Same as `selfplay --silent`
+    #[arg(short, long, default_value_t = false, hide = true)]
+    pub silent: bool,
+}
+
+impl Args {
+    /// Info by default; `RUST_LOG` still takes precedence when set.
+    pub fn log_level(&self) -> log::LevelFilter {
+        match (self.verbose, self.quiet) {
+            (0, 0) => log::LevelFilter::Info,
+            (0, 1) => log::LevelFilter::Error,
+            (0, _) => log::LevelFilter::Off,
+            (1, _) => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        }
+    }
 
-    /// How many secodns the bot is allowed to think
-    #[arg(short, long, value_name = "THINKING_SECONDS", default_value_t = 5)]
-    pub think_time: u64,
+    /// `--notation`, or what the legacy mode flags always used: coordinates
+    /// with a subcommand, raw indices without one.
+    pub fn notation(&self) -> Notation {
+        match (self.position.notation, &self.command) {
+            (Some(notation), _) => notation,
+            (None, Some(_)) => Notation::Coord,
+            (None, None) => Notation::Index,
+        }
+    }
 
-    /// Will not print the board for each move
-    #[arg(short, long, value_name = "SILENT", default_value_t = false)]
-    pub silent: bool,
+    /// `--player`, or by default `to_move` with a subcommand and X without
+    /// one, as the legacy mode flags always did.
+    pub fn player(&self, to_move: Player) -> Player {
+        match (self.position.player, &self.command) {
+            (Some(player), _) => player,
+            (None, Some(_)) => to_move,
+            (None, None) => Player::X,
+        }
+    }
+
+    /// The subcommand to run, falling back to the legacy mode flags.
+    pub fn command(&self) -> Command {
+        if let Some(command) = &self.command {
+            return command.clone();
+        }
+
+        let legacy = &self.legacy;
+        if let Some(addr) = &legacy.serve {
+            Command::Serve { addr: addr.clone() }
+        } else if legacy.engine {
+            Command::Engine
+        } else if legacy.tui {
+            Command::Play {
+                watch: legacy.watch,
+            }
+        } else if legacy.next_move {
+            Command::Move {
+                output_file: legacy.output_file.clone(),
+                output_type: legacy.output_type.clone(),
+            }
+        } else {
+            Command::Selfplay {
+                silent: legacy.silent,
+            }
+        }
+    }
 }
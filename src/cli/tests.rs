@@ -0,0 +1,44 @@
+use clap::Parser;
+
+use crate::board::{Notation, Player};
+use crate::cli::Args;
+
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from([&["rs-titato"], args].concat()).expect("arguments should parse")
+    }
+
+    #[test]
+    fn switches_override_the_profile() {
+        assert_eq!(parse(&["move"]).bot.profile().rave, None);
+        assert_eq!(parse(&["--rave", "move"]).bot.profile().rave, Some(true));
+        assert_eq!(
+            parse(&["--no-rave", "move"]).bot.profile().rave,
+            Some(false)
+        );
+        assert_eq!(
+            parse(&["--rave", "--no-rave", "move"]).bot.profile().rave,
+            Some(false)
+        );
+        assert_eq!(
+            parse(&["--no-pattern-priors", "move"])
+                .bot
+                .profile()
+                .pattern_priors,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn legacy_flags_keep_old_defaults() {
+        let legacy = parse(&["--next-move"]);
+        assert_eq!(legacy.notation(), Notation::Index);
+        assert_eq!(legacy.player(Player::O), Player::X);
+
+        let command = parse(&["move"]);
+        assert_eq!(command.notation(), Notation::Coord);
+        assert_eq!(command.player(Player::O), Player::O);
+    }
+}
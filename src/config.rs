@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::board::Variant;
+use crate::mct_bot::{BotConfig, Difficulty, RolloutPolicy};
+
+#[cfg(test)]
+mod tests;
+
+/// The profile used when `--profile` is not given, if the file has one.
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(strum_macros::Display, Debug)]
+pub enum ConfigError {
+    UnknownProfile,
+    InvalidParameter,
+}
+
+impl Error for ConfigError {}
+
+/// A shared setup, e.g.
+///
+/// ```toml
+/// [rules]
+/// n = 15
+/// k = 5
+/// variant = "exact"
+///
+/// [profiles.default]
+/// think_time = 2.5
+/// exploration = 1.2
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub rules: Rules,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    pub n: Option<i16>,
+    pub k: Option<i8>,
+    pub variant: Option<Variant>,
+}
+
+/// A named set of bot settings. Parameters it leaves out keep the built-in
+/// defaults.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// A preset the other settings are laid over
+    pub difficulty: Option<Difficulty>,
+    /// Seconds per move
+    pub think_time: Option<f64>,
+    pub exploration: Option<f32>,
+    pub widening: Option<f32>,
+    pub neighbour_chance: Option<f32>,
+    pub win_value: Option<f32>,
+    pub draw_value: Option<f32>,
+    pub rollout: Option<RolloutPolicy>,
+    pub rave: Option<bool>,
+    /// Plies before a playout is scored by the pattern evaluator
+    pub rollout_cutoff: Option<usize>,
+    pub pattern_priors: Option<bool>,
+    pub prior_weight: Option<f32>,
+    /// The most nodes the search tree may hold
+    pub max_nodes: Option<usize>,
+    /// The most positions the threat search visits per question
+    pub threat_nodes: Option<u64>,
+}
+
+impl Config {
+    pub fn load(filename: &str) -> Result<Self, Box<dyn Error>> {
+        Config::parse(&fs::read_to_string(filename)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let config: Config = toml::from_str(contents)?;
+        for profile in config.profiles.values() {
+            profile.validate()?;
+        }
+
+        Ok(config)
+    }
+
+    /// The named profile, or `default` when no name is given. Asking for a
+    /// profile the file doesn't have is an error, leaving out the name is not.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, ConfigError> {
+        match name {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .cloned()
+                .ok_or(ConfigError::UnknownProfile),
+            None => Ok(self
+                .profiles
+                .get(DEFAULT_PROFILE)
+                .cloned()
+                .unwrap_or_default()),
+        }
+    }
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let in_unit = |v: Option<f32>| v.is_none_or(|v| (0.0..=1.0).contains(&v));
+        let non_negative = |v: Option<f32>| v.is_none_or(|v| v >= 0.0);
+
+        if !in_unit(self.neighbour_chance)
+            || !non_negative(self.exploration)
+            || !non_negative(self.widening)
+            || !non_negative(self.win_value)
+            || !non_negative(self.draw_value)
+            || !non_negative(self.prior_weight)
+            || self
+                .think_time
+                .is_some_and(|t| Duration::try_from_secs_f64(t).is_err())
+        {
+            return Err(ConfigError::InvalidParameter);
+        }
+
+        Ok(())
+    }
+
+    /// Fills in this profile's gaps from `fallback`.
+    pub fn or(self, fallback: Profile) -> Profile {
+        Profile {
+            difficulty: self.difficulty.or(fallback.difficulty),
+            think_time: self.think_time.or(fallback.think_time),
+            exploration: self.exploration.or(fallback.exploration),
+            widening: self.widening.or(fallback.widening),
+            neighbour_chance: self.neighbour_chance.or(fallback.neighbour_chance),
+            win_value: self.win_value.or(fallback.win_value),
+            draw_value: self.draw_value.or(fallback.draw_value),
+            rollout: self.rollout.or(fallback.rollout),
+            rave: self.rave.or(fallback.rave),
+            rollout_cutoff: self.rollout_cutoff.or(fallback.rollout_cutoff),
+            pattern_priors: self.pattern_priors.or(fallback.pattern_priors),
+            prior_weight: self.prior_weight.or(fallback.prior_weight),
+            max_nodes: self.max_nodes.or(fallback.max_nodes),
+            threat_nodes: self.threat_nodes.or(fallback.threat_nodes),
+        }
+    }
+
+    /// The profile laid over its difficulty preset, or over the built-in bot
+    /// defaults. Assumes the profile has been validated.
+    pub fn bot_config(&self) -> BotConfig {
+        let mut config = self.difficulty.map(Difficulty::config).unwrap_or_default();
+        if let Some(think_time) = self.think_time {
+            config = config.thinking_time(Duration::from_secs_f64(think_time));
+        }
+        if let Some(exploration) = self.exploration {
+            config = config.exploration(exploration);
+        }
+        if let Some(widening) = self.widening {
+            config = config.widening(widening);
+        }
+        if let Some(neighbour_chance) = self.neighbour_chance {
+            config = config.neighbour_chance(neighbour_chance);
+        }
+        if let Some(win_value) = self.win_value {
+            config = config.win_value(win_value);
+        }
+        if let Some(draw_value) = self.draw_value {
+            config = config.draw_value(draw_value);
+        }
+        if let Some(rollout) = self.rollout {
+            config = config.rollout(rollout);
+        }
+        if let Some(rave) = self.rave {
+            config = config.rave(rave);
+        }
+        if self.rollout_cutoff.is_some() {
+            config = config.rollout_cutoff(self.rollout_cutoff);
+        }
+        if let Some(pattern_priors) = self.pattern_priors {
+            config = config.pattern_priors(pattern_priors);
+        }
+        if let Some(prior_weight) = self.prior_weight {
+            config = config.prior_weight(prior_weight);
+        }
+        if let Some(max_nodes) = self.max_nodes {
+            config = config.max_nodes(max_nodes);
+        }
+        if let Some(threat_nodes) = self.threat_nodes {
+            config = config.threat_nodes(threat_nodes);
+        }
+
+        config
+    }
+}
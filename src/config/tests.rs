@@ -0,0 +1,95 @@
+use crate::board::Variant;
+use crate::config::{Config, Profile};
+use crate::mct_bot::{BotConfig, Difficulty, RolloutPolicy};
+use std::time::Duration;
+
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [rules]
+        n = 15
+        k = 5
+        variant = "exact"
+
+        [profiles.default]
+        think_time = 2.5
+
+        [profiles.wild]
+        exploration = 2.0
+        draw_value = 0.5
+        rollout = "local"
+    "#;
+
+    #[test]
+    fn parse_rules_and_profiles() {
+        let config = Config::parse(CONFIG).expect("config should parse");
+
+        assert_eq!(config.rules.n, Some(15));
+        assert_eq!(config.rules.k, Some(5));
+        assert_eq!(config.rules.variant, Some(Variant::Exact));
+
+        let default = config.profile(None).expect("default profile");
+        assert_eq!(
+            default.bot_config(),
+            BotConfig::new().thinking_time(Duration::from_millis(2500))
+        );
+
+        let wild = config.profile(Some("wild")).expect("wild profile");
+        assert_eq!(wild.think_time, None);
+        assert_eq!(
+            wild.bot_config(),
+            BotConfig::new()
+                .exploration(2.0)
+                .draw_value(0.5)
+                .rollout(RolloutPolicy::Local)
+        );
+    }
+
+    #[test]
+    fn missing_profiles() {
+        let config = Config::parse(CONFIG).expect("config should parse");
+        assert!(config.profile(Some("tame")).is_err());
+
+        let empty = Config::parse("").expect("empty config should parse");
+        assert_eq!(empty.profile(None).expect("no profile"), Profile::default());
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(Config::parse("[profiles.x]\nneighbour_chance = 1.5").is_err());
+        assert!(Config::parse("[profiles.x]\nexplore = 1.0").is_err());
+        assert!(Config::parse("[rules]\nvariant = \"renju\"").is_err());
+    }
+
+    #[test]
+    fn flags_override_profile() {
+        let flags = Profile {
+            exploration: Some(0.5),
+            ..Profile::default()
+        };
+        let profile = Profile {
+            think_time: Some(1.0),
+            exploration: Some(3.0),
+            ..Profile::default()
+        };
+
+        let merged = flags.or(profile);
+        assert_eq!(merged.exploration, Some(0.5));
+        assert_eq!(merged.think_time, Some(1.0));
+    }
+
+    #[test]
+    fn difficulty_preset() {
+        let config = Config::parse("[profiles.casual]\ndifficulty = \"easy\"\nthink_time = 0.5")
+            .expect("config should parse");
+        let casual = config.profile(Some("casual")).expect("casual profile");
+
+        assert_eq!(
+            casual.bot_config(),
+            Difficulty::Easy
+                .config()
+                .thinking_time(Duration::from_millis(500))
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::{self, Board, Notation, Player, Variant};
+use crate::mct_bot::{Bot, Clock, Ponder};
+
+#[cfg(test)]
+mod tests;
+
+const ABOUT: &str = concat!(
+    r#"name="rs-titato", version=""#,
+    env!("CARGO_PKG_VERSION"),
+    r#"", country="NO""#
+);
+const SAFETY_MARGIN: Duration = Duration::from_millis(50);
+const MIN_THINKING_TIME: Duration = Duration::from_millis(10);
+/// How often input is checked for `STOP` while thinking.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const OWN: Player = Player::X;
+const OPPONENT: Player = Player::O;
+
+/// Input lines, read on their own thread so that commands can arrive while
+/// the bot is thinking. Commands that can't be handled mid-search wait in
+/// `pending`.
+struct Input {
+    lines: Receiver<io::Result<String>>,
+    pending: VecDeque<String>,
+}
+
+impl Input {
+    fn next(&mut self) -> io::Result<Option<String>> {
+        if let Some(line) = self.pending.pop_front() {
+            return Ok(Some(line));
+        }
+
+        match self.lines.recv() {
+            Ok(line) => line.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// A brain speaking the Gomocup (piskvork) protocol: one command per line on
+/// input, moves answered as `x,y` with the origin in the top left corner.
+pub struct Engine {
+    board: Option<Board>,
+    k: i8,
+    variant: Variant,
+    bot: Option<Bot>,
+    ponder: Option<Ponder>,
+    pondering: bool,
+    /// From `INFO timeout_turn`, less the safety margin
+    turn_limit: Option<Duration>,
+    /// From `INFO timeout_match`; none or zero means no time control
+    match_limit: Option<Duration>,
+    /// From `INFO time_left`, sent by the manager before each turn
+    time_left: Option<Duration>,
+}
+
+impl Engine {
+    pub fn new(bot: Bot, k: i8) -> Self {
+        Self {
+            board: None,
+            k,
+            variant: Variant::Freestyle,
+            bot: Some(bot),
+            ponder: None,
+            pondering: false,
+            turn_limit: None,
+            match_limit: None,
+            time_left: None,
+        }
+    }
+
+    /// Keep searching on the opponent's time, between our move and theirs.
+    pub fn with_pondering(mut self, pondering: bool) -> Self {
+        self.pondering = pondering;
+        self
+    }
+
+    /// The bot, stopping any pondering first.
+    fn bot(&mut self) -> &mut Bot {
+        if let Some(ponder) = self.ponder.take() {
+            self.bot = Some(ponder.stop());
+        }
+
+        self.bot.as_mut().expect("bot is either idle or pondering")
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    fn new_board(&self, n: i16, cells: Option<Vec<Option<Player>>>) -> Board {
+        match cells {
+            Some(cells) => Board::new_from_state(n, self.k, cells),
+            None => Board::new(n, self.k),
+        }
+        .with_variant(self.variant)
+    }
+
+    /// Reads commands until `END` or the end of the input. `STOP` (or
+    /// `YXSTOP`) while thinking makes the bot answer with its best move so
+    /// far.
+    pub fn run<R: BufRead + Send + 'static, W: Write>(
+        &mut self,
+        input: R,
+        output: &mut W,
+    ) -> Result<(), Box<dyn Error>> {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in input.lines() {
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+        let mut input = Input {
+            lines,
+            pending: VecDeque::new(),
+        };
+
+        while let Some(line) = input.next()? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            log::debug!("engine received: {line}");
+            let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+            let command = command.to_ascii_uppercase();
+            let args = args.trim();
+
+            match command.as_str() {
+                "START" => self.start(args, output)?,
+                "RESTART" => {
+                    self.board = self.board.as_ref().map(|b| self.new_board(b.n(), None));
+                    writeln!(output, "OK")?;
+                }
+                "BEGIN" => self.think(output, &mut input)?,
+                "TURN" => match self.place(args, OPPONENT) {
+                    Ok(()) => self.think(output, &mut input)?,
+                    Err(e) => writeln!(output, "ERROR {e}")?,
+                },
+                "BOARD" => {
+                    let mut stones = vec![];
+                    while let Some(line) = input.next()? {
+                        if line.trim().eq_ignore_ascii_case("DONE") {
+                            break;
+                        }
+                        stones.push(line);
+                    }
+
+                    match self.load(&stones) {
+                        Ok(()) => self.think(output, &mut input)?,
+                        Err(e) => writeln!(output, "ERROR {e}")?,
+                    }
+                }
+                "TAKEBACK" => match self.take_back(args) {
+                    Ok(()) => writeln!(output, "OK")?,
+                    Err(e) => writeln!(output, "ERROR {e}")?,
+                },
+                "INFO" => self.info(args),
+                "ABOUT" => writeln!(output, "{ABOUT}")?,
+                "END" => return Ok(()),
+                _ => writeln!(output, "UNKNOWN command {command}")?,
+            }
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn start(&mut self, args: &str, output: &mut impl Write) -> Result<(), Box<dyn Error>> {
+        match args.parse::<i16>() {
+            Ok(n) if n >= 5 && board::check_rules(n, self.k).is_ok() => {
+                self.board = Some(self.new_board(n, None));
+                writeln!(output, "OK")?;
+            }
+            _ => writeln!(output, "ERROR unsupported size {args}")?,
+        }
+
+        Ok(())
+    }
+
+    fn info(&mut self, args: &str) {
+        let (key, value) = args.split_once(' ').unwrap_or((args, ""));
+        let millis = value.trim().parse::<u64>().ok().map(Duration::from_millis);
+        if key.eq_ignore_ascii_case("timeout_turn")
+            && let Some(timeout) = millis
+        {
+            let thinking_time = timeout.saturating_sub(SAFETY_MARGIN).max(MIN_THINKING_TIME);
+            self.turn_limit = Some(thinking_time);
+            self.bot().set_thinking_time(thinking_time);
+        }
+        if key.eq_ignore_ascii_case("timeout_match") {
+            self.match_limit = millis;
+        }
+        if key.eq_ignore_ascii_case("time_left") {
+            self.time_left = millis;
+        }
+
+        // Bit 1 of the rule asks for exactly five; the other bits (continuous
+        // games, renju) are not supported and ignored.
+        if key.eq_ignore_ascii_case("rule")
+            && let Ok(rule) = value.trim().parse::<u32>()
+        {
+            self.variant = if rule & 1 == 1 {
+                Variant::Exact
+            } else {
+                Variant::Freestyle
+            };
+            self.board = self
+                .board
+                .as_ref()
+                .map(|b| b.clone().with_variant(self.variant));
+        }
+    }
+
+    /// The game clock as last reported by the manager, if the match is
+    /// played under time control.
+    fn clock(&self) -> Option<Clock> {
+        let match_limit = self.match_limit.filter(|t| !t.is_zero())?;
+        let time_left = self.time_left.unwrap_or(match_limit);
+        let clock = Clock::new(time_left.saturating_sub(SAFETY_MARGIN), Duration::ZERO);
+
+        Some(match self.turn_limit {
+            Some(turn_limit) => clock.with_max_per_move(turn_limit),
+            None => clock,
+        })
+    }
+
+    fn board(&mut self) -> Result<&mut Board, Box<dyn Error>> {
+        self.board
+            .as_mut()
+            .ok_or_else(|| Box::from("no game started"))
+    }
+
+    fn place(&mut self, args: &str, player: Player) -> Result<(), Box<dyn Error>> {
+        let board = self.board()?;
+        let m = parse_coords(args, board.n())?;
+        board.apply_move(m, player)?;
+        Ok(())
+    }
+
+    fn load(&mut self, stones: &[String]) -> Result<(), Box<dyn Error>> {
+        let n = self.board()?.n();
+        let mut cells = vec![None; (n * n) as usize];
+
+        for stone in stones {
+            let (coords, field) = stone.trim().rsplit_once(',').ok_or("malformed stone")?;
+            let m = parse_coords(coords, n)? as usize;
+            if cells[m].is_some() {
+                return Err(Box::from("cell already taken"));
+            }
+            cells[m] = match field.trim() {
+                "1" => Some(OWN),
+                "2" | "3" => Some(OPPONENT),
+                _ => return Err(Box::from("malformed stone")),
+            };
+        }
+
+        self.board = Some(self.new_board(n, Some(cells)));
+        Ok(())
+    }
+
+    fn take_back(&mut self, args: &str) -> Result<(), Box<dyn Error>> {
+        let board = self.board()?;
+        let n = board.n();
+        let m = parse_coords(args, n)? as usize;
+
+        let mut cells = board.cells().to_vec();
+        if cells[m].take().is_none() {
+            return Err(Box::from("no stone to take back"));
+        }
+
+        self.board = Some(self.new_board(n, Some(cells)));
+        Ok(())
+    }
+
+    fn think(&mut self, output: &mut impl Write, input: &mut Input) -> Result<(), Box<dyn Error>> {
+        let board = match &self.board {
+            Some(board) => board.clone(),
+            None => {
+                writeln!(output, "ERROR no game started")?;
+                return Ok(());
+            }
+        };
+
+        if let Some(clock) = self.clock() {
+            self.bot().set_clock(Some(clock));
+        }
+        self.bot();
+        let bot = self.bot.take().expect("bot should be idle");
+        let search = bot.start_search(&board, OWN);
+        while !search.is_finished() {
+            match input.lines.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => {
+                    let line = line?;
+                    let command = line.trim().to_ascii_uppercase();
+                    if command == "STOP" || command == "YXSTOP" {
+                        search.stop_handle().stop();
+                    } else {
+                        if command == "END" {
+                            search.stop_handle().stop();
+                        }
+                        input.pending.push_back(line);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let (bot, m) = search.join();
+        self.bot = Some(bot);
+        let m = match m {
+            Ok(m) => m,
+            Err(e) => {
+                writeln!(output, "ERROR {e}")?;
+                return Ok(());
+            }
+        };
+        let winner = self.board()?.apply_move(m, OWN)?;
+
+        let n = board.n();
+        let iterations = self.bot().iterations();
+        writeln!(
+            output,
+            "MESSAGE {} after {iterations} iterations",
+            Notation::Coord.format(m, n),
+        )?;
+        writeln!(output, "{},{}", m % n, m / n)?;
+
+        let board = self.board()?.clone();
+        if self.pondering
+            && winner.is_none()
+            && !board.is_tie()
+            && let Some(bot) = self.bot.take()
+        {
+            self.ponder = Some(bot.ponder(&board, OPPONENT));
+        }
+        Ok(())
+    }
+}
+
+fn parse_coords(s: &str, n: i16) -> Result<i16, Box<dyn Error>> {
+    let (x, y) = s.split_once(',').ok_or("malformed coordinates")?;
+    let (x, y): (i16, i16) = (x.trim().parse()?, y.trim().parse()?);
+    if x < 0 || y < 0 || x >= n || y >= n {
+        return Err(Box::from("coordinates outside the board"));
+    }
+
+    Ok(y * n + x)
+}
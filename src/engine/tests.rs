@@ -0,0 +1,123 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use crate::engine::Engine;
+use crate::mct_bot::Bot;
+
+const TEST_THINKING_TIME: Duration = Duration::new(1, 0);
+
+mod tests {
+    use super::*;
+
+    fn run(input: &str) -> Vec<String> {
+        let mut engine = Engine::new(Bot::new(TEST_THINKING_TIME), 5);
+        let mut output = vec![];
+        engine
+            .run(Cursor::new(input.to_string()), &mut output)
+            .expect("engine should run");
+
+        String::from_utf8(output)
+            .expect("output should be utf-8")
+            .lines()
+            .filter(|l| !l.starts_with("MESSAGE"))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn start_and_about() {
+        let output = run("START 15\nABOUT\nSTART 2\nSTART 200\nFOO\nEND\nSTART 15\n");
+
+        assert_eq!(output[0], "OK");
+        assert!(output[1].starts_with("name=\"rs-titato\""));
+        assert!(output[1].contains(concat!("version=\"", env!("CARGO_PKG_VERSION"), "\"")));
+        assert_eq!(output[2], "ERROR unsupported size 2");
+        assert_eq!(output[3], "ERROR unsupported size 200");
+        assert!(output[4].starts_with("UNKNOWN"));
+        assert_eq!(output.len(), 5);
+    }
+
+    #[test]
+    fn board_finds_win() {
+        let output = run(
+            "START 10\nINFO timeout_turn 100\nBOARD\n2,3,1\n3,3,1\n4,3,1\n5,3,1\n0,0,2\n0,1,2\n0,2,2\n0,4,2\nDONE\nEND\n",
+        );
+
+        assert_eq!(output[0], "OK");
+        assert!(output[1] == "1,3" || output[1] == "6,3");
+    }
+
+    #[test]
+    fn turn_blocks_and_take_back() {
+        let output = run(
+            "START 10\nINFO timeout_turn 100\nBOARD\n2,3,2\n3,3,2\n4,3,2\n0,0,1\n0,9,1\n9,0,1\nDONE\nTURN 5,3\nTAKEBACK 5,3\nTAKEBACK 5,3\nEND\n",
+        );
+
+        assert_eq!(output[0], "OK");
+        assert!(output[2] == "1,3" || output[2] == "6,3");
+        assert_eq!(output[3], "OK");
+        assert!(output[4].starts_with("ERROR"));
+    }
+
+    #[test]
+    fn board_rejects_duplicate_stones() {
+        let output = run("START 10\nBOARD\n2,3,1\n2,3,2\nDONE\nEND\n");
+
+        assert_eq!(output[1], "ERROR cell already taken");
+    }
+
+    #[test]
+    fn turn_outside_board() {
+        let output = run("START 10\nTURN 10,3\nEND\n");
+
+        assert!(output[1].starts_with("ERROR"));
+    }
+
+    #[test]
+    fn pondering_between_turns() {
+        let mut engine = Engine::new(Bot::new(TEST_THINKING_TIME), 5).with_pondering(true);
+        let mut output = vec![];
+        engine
+            .run(
+                // No END: it would arrive at once and cut the first search
+                // short, and a barely searched move may land on 0,0 or 0,1.
+                Cursor::new(
+                    "START 10\nINFO timeout_turn 100\nBEGIN\nTURN 0,0\nTURN 0,1\n".to_string(),
+                ),
+                &mut output,
+            )
+            .expect("engine should run");
+
+        let output = String::from_utf8(output).expect("output should be utf-8");
+        let moves: Vec<&str> = output
+            .lines()
+            .filter(|l| !l.starts_with("MESSAGE"))
+            .skip(1)
+            .collect();
+        assert_eq!(moves.len(), 3);
+        assert!(moves.iter().all(|m| !m.starts_with("ERROR")));
+    }
+
+    #[test]
+    fn stop_while_thinking() {
+        let started = std::time::Instant::now();
+        let output = run("START 10\nINFO timeout_turn 10000\nBEGIN\nSTOP\nABOUT\nEND\n");
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(output[0], "OK");
+        assert!(output[1].contains(','));
+        assert!(output[2].starts_with("name="));
+    }
+
+    #[test]
+    fn time_left_limits_thinking() {
+        let started = std::time::Instant::now();
+        let output = run(
+            "START 15\nINFO timeout_turn 5000\nINFO timeout_match 100000\nINFO time_left 400\nBEGIN\nEND\n",
+        );
+
+        assert_eq!(output[0], "OK");
+        assert!(output[1].contains(','));
+        assert!(started.elapsed() < TEST_THINKING_TIME);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::board::{Board, Player, Variant};
+
+#[cfg(test)]
+mod tests;
+
+/// The score of a completed line, beyond any total of patterns.
+pub const WIN_SCORE: i32 = 1_000_000;
+
+const DIRECTIONS: [(i16, i16); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+/// Longer runs score as if they had this many stones, which keeps every
+/// score in range however large k is.
+const MAX_SCORED_RUN: i16 = 6;
+
+/// Static evaluation of `board` for `player`: every run of 2 to k-1 stones
+/// that still has room for a line of k scores for its owner, open runs (free
+/// on both ends) far more than closed ones, and the opponent's runs count
+/// against `player`. A completed line scores `WIN_SCORE`, and nothing else
+/// reaches it, however many runs the board holds.
+pub fn evaluate(board: &Board, player: Player) -> i32 {
+    if let Some(&m) = board.winning_line().first() {
+        return if board.cells()[m as usize] == Some(player) {
+            WIN_SCORE
+        } else {
+            -WIN_SCORE
+        };
+    }
+
+    let n = board.n();
+    let mut score: i64 = 0;
+    for (m, &cell) in board.cells().iter().enumerate() {
+        let Some(owner) = cell else {
+            continue;
+        };
+
+        let (x, y) = (m as i16 % n, m as i16 / n);
+        for (dx, dy) in DIRECTIONS {
+            // Count each run once, from its first stone.
+            if cell_at(board, x - dx, y - dy) == Some(Some(owner)) {
+                continue;
+            }
+
+            let length = 1 + stretch(board, x, y, dx, dy, |c| c == Some(owner));
+            let value = i64::from(run_value(board, owner, (x, y), length, (dx, dy)));
+            score += if owner == player { value } else { -value };
+        }
+    }
+
+    score.clamp(i64::from(1 - WIN_SCORE), i64::from(WIN_SCORE - 1)) as i32
+}
+
+/// How much playing `m` is worth to `player`: the runs it would make for
+/// them plus the runs it would take from the opponent.
+pub fn score_move(board: &Board, m: i16, player: Player) -> i32 {
+    let n = board.n();
+    let (x, y) = (m % n, m / n);
+
+    let mut score = 0;
+    for owner in [player, player.next()] {
+        for (dx, dy) in DIRECTIONS {
+            let back = stretch(board, x, y, -dx, -dy, |c| c == Some(owner));
+            let forward = stretch(board, x, y, dx, dy, |c| c == Some(owner));
+            let start = (x - back * dx, y - back * dy);
+            score += run_value(board, owner, start, 1 + back + forward, (dx, dy));
+        }
+    }
+
+    score
+}
+
+/// The value of `owner`'s run of `length` stones from `start` in direction
+/// `dir`, judged by the cells around it. An overline under exact-k rules
+/// can never win and is worth nothing.
+fn run_value(board: &Board, owner: Player, start: (i16, i16), length: i16, dir: (i16, i16)) -> i32 {
+    let k = board.k() as i16;
+    if length > k && board.variant() == Variant::Exact {
+        return 0;
+    }
+    if length >= k {
+        return WIN_SCORE;
+    }
+    if length < 2 {
+        return 0;
+    }
+
+    let (x, y) = start;
+    let (dx, dy) = dir;
+    let free = |c: Option<Player>| c != Some(owner.next());
+    let before = stretch(board, x, y, -dx, -dy, free).min(k);
+    let (end_x, end_y) = (x + (length - 1) * dx, y + (length - 1) * dy);
+    let after = stretch(board, end_x, end_y, dx, dy, free).min(k);
+    if length + before + after < k {
+        return 0;
+    }
+
+    let closed = 1 << (3 * (length.min(MAX_SCORED_RUN) - 1));
+    match (before > 0, after > 0) {
+        (true, true) => 4 * closed,
+        _ => closed,
+    }
+}
+
+/// How many cells past `(x, y)` in direction `(dx, dy)` satisfy `accept`.
+fn stretch(
+    board: &Board,
+    x: i16,
+    y: i16,
+    dx: i16,
+    dy: i16,
+    accept: impl Fn(Option<Player>) -> bool,
+) -> i16 {
+    let mut count = 0;
+    while let Some(c) = cell_at(board, x + (count + 1) * dx, y + (count + 1) * dy) {
+        if !accept(c) {
+            break;
+        }
+        count += 1;
+    }
+
+    count
+}
+
+/// The cell at `(x, y)`, or `None` off the board.
+fn cell_at(board: &Board, x: i16, y: i16) -> Option<Option<Player>> {
+    let n = board.n();
+    if x < 0 || y < 0 || x >= n || y >= n {
+        return None;
+    }
+
+    Some(board.cells()[(y * n + x) as usize])
+}
@@ -0,0 +1,87 @@
+use crate::board::{Board, MAX_N, Player, Variant};
+use crate::eval::{WIN_SCORE, evaluate, score_move};
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_runs_beat_closed_runs() {
+        // An open two in the middle of the top row against one pushed into
+        // the corner.
+        let open = Board::from_moves(7, 4, &[(2, Player::X), (3, Player::X)]);
+        let closed = Board::from_moves(7, 4, &[(0, Player::X), (1, Player::X)]);
+
+        assert!(evaluate(&open, Player::X) > evaluate(&closed, Player::X));
+        assert!(evaluate(&closed, Player::X) > 0);
+        assert_eq!(evaluate(&open, Player::O), -evaluate(&open, Player::X));
+    }
+
+    #[test]
+    fn blocked_runs_are_worthless() {
+        // X X O at the edge of a 3 wide board can never make three.
+        let dead = Board::from_moves(3, 3, &[(0, Player::X), (1, Player::X), (2, Player::O)]);
+        assert_eq!(evaluate(&dead, Player::X), 0);
+
+        let won = Board::from_moves(3, 3, &[(0, Player::X), (1, Player::X), (2, Player::X)]);
+        assert_eq!(evaluate(&won, Player::X), WIN_SCORE);
+        assert_eq!(evaluate(&won, Player::O), -WIN_SCORE);
+    }
+
+    #[test]
+    fn moves_next_to_runs_score_higher() {
+        let b = Board::from_moves(9, 5, &[(40, Player::X), (41, Player::X), (0, Player::O)]);
+
+        assert!(score_move(&b, 42, Player::X) > score_move(&b, 80, Player::X));
+        assert!(score_move(&b, 42, Player::O) > score_move(&b, 80, Player::O));
+    }
+
+    #[test]
+    fn long_runs_stay_in_range() {
+        // Eleven stones each in the first two columns with k = 13.
+        let moves: Vec<(i16, Player)> = (0..11)
+            .flat_map(|y| [(y * 15, Player::X), (y * 15 + 1, Player::O)])
+            .collect();
+        let b = Board::from_moves(15, 13, &moves);
+
+        let score = evaluate(&b, Player::X);
+        assert!(score.abs() < WIN_SCORE);
+        assert!(score_move(&b, 11 * 15, Player::X) > 0);
+        assert!(score_move(&b, 11 * 15, Player::X) < WIN_SCORE);
+    }
+
+    #[test]
+    fn many_runs_stay_in_range() {
+        // Every line in every direction broken by a gap each seven cells:
+        // some 18k open runs of six X stones, which add up to more than an
+        // i32 holds, and none of them a win with k = 7.
+        let n = MAX_N as i16;
+        let cells = (0..n * n)
+            .map(|m| ((m % n + 3 * (m / n)) % 7 != 0).then_some(Player::X))
+            .collect();
+        let b = Board::new_from_state(n, 7, cells);
+
+        assert_eq!(evaluate(&b, Player::X), WIN_SCORE - 1);
+        assert_eq!(evaluate(&b, Player::O), 1 - WIN_SCORE);
+    }
+
+    #[test]
+    fn exact_overlines_are_worthless() {
+        // X's four on the top row is an overline when exactly three win.
+        let mut b = Board::new(7, 3).with_variant(Variant::Exact);
+        for (m, p) in [
+            (0, Player::X),
+            (48, Player::O),
+            (1, Player::X),
+            (47, Player::O),
+            (3, Player::X),
+            (40, Player::O),
+            (2, Player::X),
+        ] {
+            b.apply_move(m, p).expect("move should be valid");
+        }
+
+        assert!(b.winning_line().is_empty());
+        assert!(evaluate(&b, Player::X) < 0);
+        assert_eq!(score_move(&b, 4, Player::X), 0);
+    }
+}
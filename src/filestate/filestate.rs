@@ -1,12 +1,17 @@
-use crate::board::{self, Player};
+use crate::board::{self, Board, Notation, Player, notation};
 use file_type::FileType;
 
-use std::{error::Error, fs::File, io::Read};
+use std::{
+    error::Error,
+    fs::File,
+    io::{Read, Write},
+};
 
 #[derive(Debug, Clone, clap::ValueEnum)]
 pub enum FileState {
     Board,
     Cells,
+    Moves,
 }
 
 impl FileState {
@@ -14,6 +19,7 @@ impl FileState {
         match self {
             FileState::Board => "<board>",
             FileState::Cells => "<cells>",
+            FileState::Moves => "<moves>",
         }
     }
 }
@@ -23,12 +29,18 @@ impl FileState {
 pub enum FileError {
     UnknownFormat,
     UnknownBoardStyle,
+    InvalidMoveList,
+    InvalidBoardSize,
+    UnrepresentableState,
 }
 
 impl Error for FileError {}
 
+/// The size of a loaded board and its cells, row by row.
+type State = (usize, Vec<Option<Player>>);
+
 #[allow(dead_code)]
-pub fn get_cells(filename: &str) -> Result<(usize, Vec<Option<Player>>), Box<dyn Error>> {
+pub fn get_cells(filename: &str) -> Result<State, Box<dyn Error>> {
     let mut file = File::open(filename)?;
     let mut buffer: Vec<u8> = vec![];
 
@@ -43,7 +55,7 @@ pub fn get_cells(filename: &str) -> Result<(usize, Vec<Option<Player>>), Box<dyn
 }
 
 #[allow(dead_code)]
-fn from_text(buffer: Vec<u8>) -> Result<(usize, Vec<Option<Player>>), Box<dyn Error>> {
+fn from_text(buffer: Vec<u8>) -> Result<State, Box<dyn Error>> {
     let contents = String::from_utf8(buffer)?.replace("\r\n", "\n");
 
     if contents.starts_with(FileState::Board.prefix()) {
@@ -64,20 +76,120 @@ fn from_text(buffer: Vec<u8>) -> Result<(usize, Vec<Option<Player>>), Box<dyn Er
         );
     }
 
+    if contents.starts_with(FileState::Moves.prefix()) {
+        return from_text_moves(
+            contents
+                .strip_prefix(FileState::Moves.prefix())
+                .expect("string should start with <moves>")
+                .trim(),
+        );
+    }
+
     Err(Box::from(FileError::UnknownBoardStyle))
 }
 
 #[allow(dead_code)]
-fn from_text_cells(cell_string: &str) -> Result<(usize, Vec<Option<Player>>), Box<dyn Error>> {
-    Ok(board::from_cell_string_to_state(cell_string))
+fn from_text_cells(cell_string: &str) -> Result<State, Box<dyn Error>> {
+    Ok(board::from_cell_string_to_state(cell_string)?)
 }
 
 #[allow(dead_code)]
-fn from_text_board(board: &str) -> Result<(usize, Vec<Option<Player>>), Box<dyn Error>> {
-    Ok(board::from_board_string_to_state(board))
+fn from_text_board(board: &str) -> Result<State, Box<dyn Error>> {
+    Ok(board::from_board_string_to_state(board)?)
+}
+
+/// Reads the board size followed by the moves in play order, e.g.
+/// `5\nc3 b4 d2`. X moves first unless the list starts with `O`.
+fn from_text_moves(moves: &str) -> Result<State, Box<dyn Error>> {
+    let mut tokens = moves
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty());
+
+    let n: usize = tokens.next().ok_or(FileError::InvalidMoveList)?.parse()?;
+    if n == 0 || n > board::MAX_N {
+        return Err(Box::from(FileError::InvalidBoardSize));
+    }
+    let mut cells = vec![None; n * n];
+    let mut player = Player::X;
+    let mut tokens = tokens.peekable();
+    if let Some(&first) = tokens.peek()
+        && (first.eq_ignore_ascii_case("X") || first.eq_ignore_ascii_case("O"))
+    {
+        player = if first.eq_ignore_ascii_case("X") {
+            Player::X
+        } else {
+            Player::O
+        };
+        tokens.next();
+    }
+
+    for token in tokens {
+        let m = notation::parse_move(token, n as i16)? as usize;
+        if cells[m].is_some() {
+            return Err(Box::from(FileError::InvalidMoveList));
+        }
+        cells[m] = Some(player);
+        player = player.next();
+    }
+
+    Ok((n, cells))
+}
+
+pub fn save(
+    filename: &str,
+    style: &FileState,
+    b: &Board,
+    notation: Notation,
+) -> Result<(), Box<dyn Error>> {
+    let contents = to_text(style, b, notation)?;
+
+    let mut file = File::create(filename)?;
+    write!(file, "{contents}")?;
+    Ok(())
+}
+
+/// The full contents of a state file, prefix included.
+pub fn to_text(style: &FileState, b: &Board, notation: Notation) -> Result<String, Box<dyn Error>> {
+    let body = match style {
+        FileState::Board => board::to_board_string(b.cells(), b.n() as usize),
+        FileState::Cells => board::to_cell_string(b.cells()) + "\n",
+        FileState::Moves => to_text_moves(b, notation)?,
+    };
+
+    Ok(format!("{}\n{}", style.prefix(), body))
+}
+
+/// Any position whose stone counts differ by at most one can be written as an
+/// alternating move list; the order within each player's stones is arbitrary.
+fn to_text_moves(b: &Board, notation: Notation) -> Result<String, Box<dyn Error>> {
+    let stones = |p: Player| -> Vec<i16> {
+        (0..b.cells().len() as i16)
+            .filter(|&m| b.cells()[m as usize] == Some(p))
+            .collect()
+    };
+    let (x, o) = (stones(Player::X), stones(Player::O));
+    if x.len().abs_diff(o.len()) > 1 {
+        return Err(Box::from(FileError::UnrepresentableState));
+    }
+
+    let (first, first_stones, second_stones) = if o.len() > x.len() {
+        (Player::O, o, x)
+    } else {
+        (Player::X, x, o)
+    };
+
+    let mut moves = vec![];
+    for (i, &m) in first_stones.iter().enumerate() {
+        moves.push(notation.format(m, b.n()));
+        if let Some(&m) = second_stones.get(i) {
+            moves.push(notation.format(m, b.n()));
+        }
+    }
+
+    Ok(format!("{} {first}\n{}\n", b.n(), moves.join(" ")))
 }
 
 #[allow(dead_code)]
-fn from_image(_buffer: Vec<u8>) -> Result<(usize, Vec<Option<Player>>), Box<dyn Error>> {
+fn from_image(_buffer: Vec<u8>) -> Result<State, Box<dyn Error>> {
     Ok((0, vec![]))
 }
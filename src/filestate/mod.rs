@@ -2,3 +2,5 @@ mod filestate;
 
 pub use filestate::FileState;
 pub use filestate::get_cells;
+pub use filestate::save;
+pub use filestate::to_text;
@@ -1,45 +1,345 @@
+mod alphabeta;
 mod board;
 mod cli;
+mod config;
+mod engine;
+mod eval;
 mod filestate;
 mod mct_bot;
+mod report;
+mod server;
+mod solver;
+mod threats;
+mod tui;
 
 use clap::Parser;
 
-use crate::board::{Board, Player};
-use crate::mct_bot::Bot;
+use crate::alphabeta::AlphaBeta;
+use crate::board::{Board, Notation, Player, Renderer, Variant};
+use crate::cli::Command;
+use crate::config::Config;
+use crate::filestate::FileState;
+use crate::mct_bot::{Bot, BotConfig, Clock};
+use crate::report::{
+    AnalysisRecord, BenchRecord, BenchSummary, EvaluationRecord, Format, GameRecord, MoveRecord,
+    SolveRecord, StateRecord,
+};
+use crate::solver::Solver;
 
 use std::error::Error;
-use std::time::Duration;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::process::ExitCode;
+use std::time::Instant;
 
-fn main() -> Result<(), Box<dyn Error>> {
+/// The command ran but failed, e.g. the game was already over.
+const EXIT_FAILURE: u8 = 1;
+/// The state file or `--moves` could not be turned into a position. Bad
+/// arguments exit with 2, which clap reserves for itself.
+const EXIT_INVALID_POSITION: u8 = 3;
+/// The config file could not be read or lacks the requested profile, or
+/// the board size and row length don't make a game.
+const EXIT_INVALID_CONFIG: u8 = 4;
+
+const DEFAULT_N: i16 = 3;
+const DEFAULT_K: i8 = 3;
+
+/// Marks errors from loading the position so they get their own exit code.
+#[derive(Debug)]
+struct InvalidPosition(Box<dyn Error>);
+
+impl fmt::Display for InvalidPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid position: {}", self.0)
+    }
+}
+
+impl Error for InvalidPosition {}
+
+#[derive(Debug)]
+struct InvalidConfig(Box<dyn Error>);
+
+impl fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid config: {}", self.0)
+    }
+}
+
+impl Error for InvalidConfig {}
+
+/// The rules and bot setup after layering flags over the config file over the
+/// built-in defaults.
+struct Settings {
+    n: i16,
+    k: i8,
+    variant: Variant,
+    bot: BotConfig,
+    clock: Option<Clock>,
+    /// Whether `bot` has the built-in thinking time, which the server
+    /// replaces with a shorter one of its own
+    default_think_time: bool,
+}
+
+impl Settings {
+    fn resolve(cli: &cli::Args) -> Result<Self, Box<dyn Error>> {
+        let config = match &cli.bot.config {
+            Some(f) => Config::load(f).map_err(InvalidConfig)?,
+            None => Config::default(),
+        };
+        let profile = config
+            .profile(cli.bot.profile.as_deref())
+            .map_err(|e| InvalidConfig(Box::from(e)))?;
+        let profile = cli.bot.profile().or(profile);
+        profile
+            .validate()
+            .map_err(|e| InvalidConfig(Box::from(e)))?;
+
+        let settings = Self {
+            n: cli.rules.n.or(config.rules.n).unwrap_or(DEFAULT_N),
+            k: cli.rules.k.or(config.rules.k).unwrap_or(DEFAULT_K),
+            variant: cli
+                .rules
+                .variant
+                .or(config.rules.variant)
+                .unwrap_or_default(),
+            bot: profile.bot_config(),
+            clock: cli.bot.clock(),
+            default_think_time: profile.think_time.is_none() && profile.difficulty.is_none(),
+        };
+
+        // A state file brings its own size, checked against k once loaded.
+        let n = match cli.position.state_file {
+            Some(_) => board::MAX_N as i16,
+            None => settings.n,
+        };
+        board::check_rules(n, settings.k).map_err(|e| InvalidConfig(Box::from(e)))?;
+
+        Ok(settings)
+    }
+
+    fn bot(&self) -> Bot {
+        let mut bot = Bot::with_config(self.bot);
+        bot.set_clock(self.clock);
+        bot
+    }
+}
+
+fn main() -> ExitCode {
     let cli = cli::Args::parse();
+    env_logger::Builder::new()
+        .filter_level(cli.log_level())
+        .parse_default_env()
+        .init();
 
-    let mut b: Board;
-    if let Some(f) = cli.state_file {
-        let (n, cells) = filestate::get_cells(f.as_str())?;
-        b = Board::new_from_state(n as i16, cli.k, cells);
-    } else {
-        b = Board::new(cli.n, cli.k);
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            if e.is::<InvalidPosition>() {
+                ExitCode::from(EXIT_INVALID_POSITION)
+            } else if e.is::<InvalidConfig>() {
+                ExitCode::from(EXIT_INVALID_CONFIG)
+            } else {
+                ExitCode::from(EXIT_FAILURE)
+            }
+        }
     }
+}
 
-    let mut bot = Bot::new(Duration::new(cli.think_time, 0));
+fn run(cli: &cli::Args) -> Result<(), Box<dyn Error>> {
+    let settings = Settings::resolve(cli)?;
+    let (k, variant) = (settings.k, settings.variant);
+    let (format, notation) = (cli.format, cli.notation());
+    let bot = settings.bot();
+    let mut stdout = io::stdout();
 
-    if !cli.next_move {
-        return play(b, bot, cli.player, !cli.silent);
+    match cli.command() {
+        Command::Serve { addr } => {
+            let mut config = settings.bot;
+            if settings.default_think_time {
+                config = config.thinking_time(server::DEFAULT_THINK_TIME);
+            }
+            let server = server::Server::bind(&addr, k)
+                .map_err(|e| e.to_string())?
+                .with_variant(variant)
+                .with_config(config);
+            if let Some(addr) = server.local_addr() {
+                log::info!("listening on {addr}");
+            }
+            server.run();
+            Ok(())
+        }
+        Command::Engine => engine::Engine::new(bot, k)
+            .with_variant(variant)
+            .with_pondering(cli.bot.ponder)
+            .run(io::BufReader::new(io::stdin()), &mut stdout),
+        Command::Play { watch } => {
+            let (b, player) = load_position(cli, &settings)?;
+            let human = if watch { None } else { Some(player) };
+            tui::run(b, bot, player, human, notation, cli.bot.ponder)
+        }
+        Command::Selfplay { silent } => {
+            let (b, player) = load_position(cli, &settings)?;
+            let renderer = if silent { None } else { Some(renderer(cli)) };
+            play(b, bot, player, renderer, notation, format)
+        }
+        Command::Move {
+            output_file,
+            output_type,
+        } => {
+            let (b, player) = load_position(cli, &settings)?;
+            next_move(b, bot, player, output_file, output_type, notation, format)
+        }
+        Command::Analyze {
+            top,
+            dump_tree,
+            tree_format,
+            tree_depth,
+            tree_min_visits,
+        } => {
+            let (b, player) = load_position(cli, &settings)?;
+            let mut bot = bot;
+            let start = Instant::now();
+            let best = bot.find_next_move(&b, player)?;
+            if let Some(path) = dump_tree {
+                match bot.export_tree(tree_depth, tree_min_visits) {
+                    Some(tree) => tree.write(tree_format, &mut File::create(path)?)?,
+                    None => log::warn!(
+                        "{} was found without searching, no tree to dump",
+                        notation.format(best, b.n())
+                    ),
+                }
+            }
+            let candidates = bot.candidates();
+            let record = AnalysisRecord::new(
+                b.n(),
+                best,
+                player,
+                bot.iterations(),
+                start.elapsed(),
+                &candidates[..top.min(candidates.len())],
+                notation,
+            );
+            format.emit(&record, &mut stdout)
+        }
+        Command::Solve { node_limit } => {
+            let (b, player) = load_position(cli, &settings)?;
+            let start = Instant::now();
+            let solution = Solver::new()
+                .with_node_limit(node_limit)
+                .solve(&b, player)?;
+            let record = SolveRecord::new(b.n(), &solution, player, start.elapsed(), notation);
+            format.emit(&record, &mut stdout)
+        }
+        Command::Evaluate { depth } => {
+            let (b, player) = load_position(cli, &settings)?;
+            let start = Instant::now();
+            let evaluation = AlphaBeta::new(depth).search(&b, player);
+            let record =
+                EvaluationRecord::new(b.n(), &evaluation, player, depth, start.elapsed(), notation);
+            format.emit(&record, &mut stdout)
+        }
+        Command::Convert {
+            output_file,
+            output_type,
+        } => {
+            let (b, _) = load_position(cli, &settings)?;
+            match output_file {
+                Some(f) => filestate::save(&f, &output_type, &b, notation),
+                None => {
+                    let contents = filestate::to_text(&output_type, &b, notation)?;
+                    format.emit(&StateRecord { contents }, &mut stdout)
+                }
+            }
+        }
+        Command::Bench { runs } => {
+            let (b, player) = load_position(cli, &settings)?;
+            bench(b, bot, player, runs, format)
+        }
     }
+}
 
-    let next_move = bot.find_next_move(&b, cli.player)?;
-    let winner = b.apply_move(next_move, cli.player)?;
+/// Loads the state file or an empty board, then plays `--moves` on it.
+fn load_position(cli: &cli::Args, settings: &Settings) -> Result<(Board, Player), Box<dyn Error>> {
+    let (n, k) = (settings.n, settings.k);
 
-    if cli.output_file.is_none() {
-        print!("next_move={next_move}");
-        if winner.is_some() {
-            let winner = winner.unwrap();
-            print!(" winner={winner}");
+    let mut b = match &cli.position.state_file {
+        Some(f) => {
+            let (n, cells) = filestate::get_cells(f.as_str()).map_err(InvalidPosition)?;
+            board::check_rules(n as i16, k).map_err(|e| InvalidPosition(Box::from(e)))?;
+            Board::new_from_state(n as i16, k, cells)
         }
+        None => Board::new(n, k),
+    }
+    .with_variant(settings.variant);
+
+    for m in &cli.position.moves {
+        let m = cli
+            .notation()
+            .parse(m, b.n())
+            .map_err(|e| InvalidPosition(Box::from(e)))?;
+        b.apply_move(m, b.to_move()).map_err(InvalidPosition)?;
+    }
+
+    let player = cli.player(b.to_move());
+    Ok((b, player))
+}
+
+fn renderer(cli: &cli::Args) -> Renderer {
+    Renderer::new()
+        .style(cli.display.grid)
+        .coords(cli.display.coords)
+        .color(cli.display.color)
+}
+
+fn next_move(
+    mut b: Board,
+    mut bot: Bot,
+    player: Player,
+    output_file: Option<String>,
+    output_type: FileState,
+    notation: Notation,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let start = Instant::now();
+    let next_move = bot.find_next_move(&b, player)?;
+    let elapsed = start.elapsed();
+    b.apply_move(next_move, player)?;
+
+    if let Some(f) = output_file {
+        filestate::save(f.as_str(), &output_type, &b, notation)?;
+    } else {
+        let record = MoveRecord::new(&b, next_move, player, bot.iterations(), elapsed, notation);
+        format.emit(&record, &mut io::stdout())?;
+    }
+
+    Ok(())
+}
+
+fn bench(
+    b: Board,
+    mut bot: Bot,
+    player: Player,
+    runs: u32,
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    let mut total = 0.0;
+
+    for run in 1..=runs {
+        let start = Instant::now();
+        bot.find_next_move(&b, player)?;
+        let record = BenchRecord::new(run, bot.iterations(), start.elapsed());
+        total += record.iterations_per_second;
+        format.emit(&record, &mut io::stdout())?;
+    }
 
-        print!("\n");
-        return Ok(());
+    if runs > 0 {
+        let summary = BenchSummary {
+            runs,
+            mean_iterations_per_second: total / runs as f64,
+        };
+        format.emit(&summary, &mut io::stdout())?;
     }
 
     Ok(())
@@ -49,34 +349,52 @@ fn play(
     board: Board,
     bot: Bot,
     starting_player: Player,
-    verbose: bool,
+    renderer: Option<Renderer>,
+    notation: Notation,
+    format: Format,
 ) -> Result<(), Box<dyn Error>> {
     let mut bot = bot;
     let mut board = board;
     let mut winner: Option<Player>;
     let mut player = starting_player;
+    let mut moves = vec![];
+    let mut stdout = io::stdout();
+    let start = Instant::now();
 
     loop {
         if board.is_tie() {
-            println!("no dinner");
-            return Ok(());
+            let record = GameRecord::new(&board, moves, None, start.elapsed());
+            return format.emit(&record, &mut stdout);
         }
 
+        let move_start = Instant::now();
         let nm = bot.find_next_move(&board, player)?;
-        if verbose {
-            print!("Found move {nm}\n");
+        let elapsed = move_start.elapsed();
+        if renderer.is_some() {
+            log::info!("found move {}", notation.format(nm, board.n()));
         }
 
         winner = board.apply_move(nm, player)?;
-        if verbose {
-            board.print();
+        moves.push(notation.format(nm, board.n()));
+        if let Some(renderer) = &renderer {
+            match format {
+                Format::Text => {
+                    renderer.render(&board, &mut stdout)?;
+                    println!();
+                }
+                Format::Json => {
+                    let record =
+                        MoveRecord::new(&board, nm, player, bot.iterations(), elapsed, notation);
+                    format.emit(&record, &mut stdout)?;
+                }
+            }
         }
 
         player = player.next();
 
         if let Some(winner) = winner {
-            println!("chicken dinner {winner}");
-            return Ok(());
+            let record = GameRecord::new(&board, moves, Some(winner), start.elapsed());
+            return format.emit(&record, &mut stdout);
         }
     }
 }
@@ -42,27 +42,7 @@ impl BotBoard {
     }
 
     pub fn is_tactical_move(&mut self, m: i16, player: Player) -> bool {
-        let next_winner = self
-            .board
-            .apply_move(m, player.next())
-            .expect("move should be valid");
-        self.board
-            .undo_last_move()
-            .expect("undo move should be valid");
-
-        if next_winner.is_some() {
-            return true;
-        }
-
-        let self_winner = self
-            .board
-            .apply_move(m, player)
-            .expect("move should be valid");
-        self.board
-            .undo_last_move()
-            .expect("undo move should be valid");
-
-        self_winner.is_some()
+        self.board.completes_line(m, player.next()) || self.board.completes_line(m, player)
     }
 
     pub fn has_neighbour(&mut self, m: i16) -> bool {
@@ -99,37 +79,10 @@ impl BotBoard {
     }
 
     pub fn terminating_moves(&mut self, player: Player) -> (Option<i16>, Vec<i16>) {
-        let mut blocking_moves: Vec<i16> = vec![];
-
-        for (m, cell) in self.board.clone().cells().iter().enumerate() {
-            if cell.is_some() {
-                continue;
-            }
-
-            let m = m as i16;
-
-            let winner = self
-                .board
-                .apply_move(m, player)
-                .expect("move should be valid");
-            self.board.undo_last_move().expect("undo should be valid");
-
-            if winner.is_some_and(|p| p == player) {
-                return (Some(m), vec![]);
-            }
-
-            let next_player = player.next();
-            let winner = self
-                .board
-                .apply_move(m, next_player)
-                .expect("move should be valid");
-            self.board.undo_last_move().expect("undo should be valid");
-
-            if winner.is_some_and(|p| p == next_player) {
-                blocking_moves.push(m);
-            }
+        if let Some(&m) = self.board.winning_moves(player).first() {
+            return (Some(m), vec![]);
         }
 
-        (None, blocking_moves)
+        (None, self.board.winning_moves(player.next()))
     }
 }
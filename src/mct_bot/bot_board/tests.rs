@@ -12,7 +12,7 @@ mod tests {
         [ ][X][O]
         [ ][ ][X]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut raw_test_board = Board::new(n as i16, 3);
         let mut test_board = BotBoard::new(raw_test_board.clone());
         for (m, p) in cells.iter().enumerate() {
@@ -38,7 +38,7 @@ mod tests {
         [ ][X][O]
         [ ][ ][X]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut raw_test_board = Board::new(n as i16, 3);
         let mut test_board = BotBoard::new(raw_test_board.clone());
         for (m, p) in cells.iter().enumerate() {
@@ -72,7 +72,7 @@ mod tests {
         [ ][ ][ ]
         [O][ ][X]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut raw_test_board = Board::new(n as i16, 3);
         let mut test_board = BotBoard::new(raw_test_board.clone());
         for (m, p) in cells.iter().enumerate() {
@@ -104,7 +104,7 @@ mod tests {
         [ ][ ][ ][ ]
         [ ][ ][ ][ ]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut raw_test_board = Board::new(n as i16, 3);
         let mut test_board = BotBoard::new(raw_test_board.clone());
         for (m, p) in cells.iter().enumerate() {
@@ -132,7 +132,7 @@ mod tests {
         [ ][ ][X][ ]
         [ ][ ][ ][ ]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut raw_test_board = Board::new(n as i16, 3);
         let mut test_board = BotBoard::new(raw_test_board.clone());
         for (m, p) in cells.iter().enumerate() {
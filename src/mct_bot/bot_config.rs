@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use crate::mct_bot::RolloutPolicy;
+use crate::threats;
+
+const THINKING_TIME: Duration = Duration::new(5, 0);
+const EXPLORATION_PARAM: f32 = 1.414;
+const WIDENING_FACTOR: f32 = 2.0;
+const NEIGHBOUR_CHANCE: f32 = 0.8;
+const WIN_VALUE: f32 = 1.;
+const DRAW_VALUE: f32 = 0.6;
+const PRIOR_WEIGHT: f32 = 1.0;
+const MAX_NODES: usize = 1_000_000;
+
+/// Everything that shapes a search, set with consuming builder methods:
+///
+/// `BotConfig::new().thinking_time(Duration::from_millis(200)).exploration(0.9)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BotConfig {
+    pub(super) thinking_time: Duration,
+    pub(super) exploration: f32,
+    pub(super) widening: f32,
+    pub(super) neighbour_chance: f32,
+    pub(super) win_value: f32,
+    pub(super) draw_value: f32,
+    pub(super) temperature: f32,
+    pub(super) tactical_shortcut: bool,
+    pub(super) rollout: RolloutPolicy,
+    pub(super) rave: bool,
+    pub(super) rollout_cutoff: Option<usize>,
+    pub(super) pattern_priors: bool,
+    pub(super) prior_weight: f32,
+    pub(super) max_nodes: usize,
+    pub(super) threat_nodes: u64,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        BotConfig::new()
+    }
+}
+
+impl BotConfig {
+    pub fn new() -> Self {
+        Self {
+            thinking_time: THINKING_TIME,
+            exploration: EXPLORATION_PARAM,
+            widening: WIDENING_FACTOR,
+            neighbour_chance: NEIGHBOUR_CHANCE,
+            win_value: WIN_VALUE,
+            draw_value: DRAW_VALUE,
+            temperature: 0.0,
+            tactical_shortcut: true,
+            rollout: RolloutPolicy::default(),
+            rave: false,
+            rollout_cutoff: None,
+            pattern_priors: false,
+            prior_weight: PRIOR_WEIGHT,
+            max_nodes: MAX_NODES,
+            threat_nodes: threats::NODE_LIMIT,
+        }
+    }
+
+    pub fn thinking_time(mut self, thinking_time: Duration) -> Self {
+        self.thinking_time = thinking_time;
+        self
+    }
+
+    /// The UCT exploration constant.
+    pub fn exploration(mut self, exploration: f32) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Progressive widening: a node with `v` visits may have up to
+    /// `widening * sqrt(v)` children.
+    pub fn widening(mut self, widening: f32) -> Self {
+        self.widening = widening;
+        self
+    }
+
+    /// How much being next to an existing stone adds to a move's prior,
+    /// clamped to 0..=1.
+    pub fn neighbour_chance(mut self, neighbour_chance: f32) -> Self {
+        self.neighbour_chance = neighbour_chance.clamp(0.0, 1.0);
+        self
+    }
+
+    /// What a won playout is worth to the winner.
+    pub fn win_value(mut self, win_value: f32) -> Self {
+        self.win_value = win_value;
+        self
+    }
+
+    /// What a drawn playout is worth to both players.
+    pub fn draw_value(mut self, draw_value: f32) -> Self {
+        self.draw_value = draw_value;
+        self
+    }
+
+    /// Sample the move by a softmax over the root visit counts instead of
+    /// always taking the most visited one. 0 is deterministic; at 1 the most
+    /// visited move is e times as likely as an unvisited one, and higher
+    /// values flatten further.
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature.max(0.0);
+        self
+    }
+
+    /// Whether immediate wins, forced blocks and forcing sequences found by
+    /// the threat search are played without searching.
+    pub fn tactical_shortcut(mut self, tactical_shortcut: bool) -> Self {
+        self.tactical_shortcut = tactical_shortcut;
+        self
+    }
+
+    /// How playouts choose their moves.
+    pub fn rollout(mut self, rollout: RolloutPolicy) -> Self {
+        self.rollout = rollout;
+        self
+    }
+
+    /// Blend all-moves-as-first statistics into the node values (RAVE).
+    pub fn rave(mut self, rave: bool) -> Self {
+        self.rave = rave;
+        self
+    }
+
+    /// End playouts after this many moves and let the pattern evaluator
+    /// decide the winner, rather than playing to the end.
+    pub fn rollout_cutoff(mut self, plies: Option<usize>) -> Self {
+        self.rollout_cutoff = plies;
+        self
+    }
+
+    /// Let the pattern evaluator's move scores count towards the priors.
+    pub fn pattern_priors(mut self, pattern_priors: bool) -> Self {
+        self.pattern_priors = pattern_priors;
+        self
+    }
+
+    /// How much a move's prior biases selection towards it, 0 for plain
+    /// UCT.
+    pub fn prior_weight(mut self, prior_weight: f32) -> Self {
+        self.prior_weight = prior_weight;
+        self
+    }
+
+    /// The most nodes the tree may hold. When it fills up, the least visited
+    /// subtrees are dropped to make room; at least 2.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes.max(2);
+        self
+    }
+
+    /// The most positions the threat search visits per question before it
+    /// gives up. It also stops when its share of the thinking time runs out.
+    pub fn threat_nodes(mut self, threat_nodes: u64) -> Self {
+        self.threat_nodes = threat_nodes;
+        self
+    }
+}
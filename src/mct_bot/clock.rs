@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests;
+
+/// The share of the remaining time a single move may use at most.
+const MAX_SHARE: f64 = 0.5;
+/// Bounds on how many more moves the bot expects to make.
+const MIN_MOVES_LEFT: f64 = 10.0;
+const MAX_MOVES_LEFT: f64 = 40.0;
+/// How much more than average a move in the middle of the game gets.
+const MIDDLEGAME_BONUS: f64 = 1.0;
+const OPENING_WEIGHT: f64 = 0.5;
+
+/// A game clock: time left for the rest of the game, plus an increment
+/// earned with every move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    remaining: Duration,
+    increment: Duration,
+    max_per_move: Option<Duration>,
+}
+
+impl Clock {
+    pub fn new(total: Duration, increment: Duration) -> Self {
+        Self {
+            remaining: total,
+            increment,
+            max_per_move: None,
+        }
+    }
+
+    /// Never spend more than `max` on a single move, whatever the clock says.
+    pub fn with_max_per_move(mut self, max: Duration) -> Self {
+        self.max_per_move = Some(max);
+        self
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// How long to think about the next move with `stones` of `cells` filled.
+    /// Time is spread over the moves that are likely left, weighted towards
+    /// the middle of the game where positions are the most complex; the
+    /// opening and a nearly full board get less.
+    pub fn allocate(&self, stones: usize, cells: usize) -> Duration {
+        let empty = cells.saturating_sub(stones) as f64;
+        let moves_left = (empty / 2.0).clamp(MIN_MOVES_LEFT, MAX_MOVES_LEFT);
+
+        let filled = stones as f64 / cells.max(1) as f64;
+        let weight = OPENING_WEIGHT + MIDDLEGAME_BONUS * 4.0 * filled * (1.0 - filled);
+
+        let budget = self.remaining.div_f64(moves_left).mul_f64(weight) + self.increment;
+        let budget = budget.min(self.remaining.mul_f64(MAX_SHARE));
+        match self.max_per_move {
+            Some(max) => budget.min(max),
+            None => budget,
+        }
+    }
+
+    /// Charges a move that took `elapsed` to the clock.
+    pub fn spend(&mut self, elapsed: Duration) {
+        self.remaining = self.remaining.saturating_sub(elapsed) + self.increment;
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use crate::mct_bot::Clock;
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn middlegame_gets_more_time() {
+        let clock = Clock::new(Duration::from_secs(60), Duration::ZERO);
+
+        let opening = clock.allocate(0, 225);
+        let middlegame = clock.allocate(110, 225);
+        assert!(middlegame > opening);
+        assert!(middlegame <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn never_spends_more_than_allowed() {
+        let clock = Clock::new(Duration::from_secs(1), Duration::from_secs(5));
+        assert_eq!(clock.allocate(4, 9), Duration::from_millis(500));
+
+        let clock = Clock::new(Duration::from_secs(60), Duration::ZERO)
+            .with_max_per_move(Duration::from_millis(200));
+        assert_eq!(clock.allocate(50, 225), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn spend_adds_increment() {
+        let mut clock = Clock::new(Duration::from_secs(10), Duration::from_secs(2));
+
+        clock.spend(Duration::from_secs(3));
+        assert_eq!(clock.remaining(), Duration::from_secs(9));
+
+        clock.spend(Duration::from_secs(30));
+        assert_eq!(clock.remaining(), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use crate::mct_bot::BotConfig;
+
+/// Preset strengths for casual opponents. Weaker levels think for less time,
+/// sample their move by a softmax over the root visit counts rather than
+/// taking the best one, and at the easiest level can overlook immediate wins
+/// and threats.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub fn config(self) -> BotConfig {
+        let (millis, temperature, tactical_shortcut) = match self {
+            Difficulty::Easy => (100, 1.0, false),
+            Difficulty::Medium => (300, 0.5, true),
+            Difficulty::Hard => (1000, 0.1, true),
+            Difficulty::Expert => (5000, 0.0, true),
+        };
+
+        BotConfig::new()
+            .thinking_time(Duration::from_millis(millis))
+            .temperature(temperature)
+            .tactical_shortcut(tactical_shortcut)
+    }
+}
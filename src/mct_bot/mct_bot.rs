@@ -1,15 +1,47 @@
 use std::error::Error;
-use std::time::{Duration, SystemTime};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use rand::seq::IndexedRandom;
 use rand::{random_range, rng};
 
-use crate::board::{Board, Player};
+use crate::board::symmetry;
+use crate::board::{BitBoard, Board, GameBoard, Notation, Player, U256, Variant};
+use crate::eval;
 use crate::mct_bot::bot_board::BotBoard;
+use crate::mct_bot::bot_config::BotConfig;
+use crate::mct_bot::clock::Clock;
+use crate::mct_bot::rollout::RolloutPolicy;
+use crate::mct_bot::search::{PROGRESS_INTERVAL, Ponder, Progress, Search, StopHandle};
+use crate::mct_bot::tree::TreeNode;
+use crate::threats::ThreatSearch;
 
 #[cfg(test)]
 mod tests;
 
+/// How many iterations pass between checks whether the search is decided.
+const DECIDED_CHECK_INTERVAL: usize = 256;
+/// The visit count at which a node's own value and its AMAF value weigh
+/// about the same.
+const RAVE_EQUIVALENCE: f32 = 1000.0;
+/// Prior scores, before they are normalised: a move that wins at once, one
+/// that stops the opponent winning, one next to a stone (scaled by
+/// `neighbour_chance`), and the divisor for pattern scores.
+const WIN_PRIOR: f32 = 1000.0;
+const BLOCK_PRIOR: f32 = 500.0;
+const NEIGHBOUR_PRIOR: f32 = 4.0;
+const PATTERN_PRIOR_SCALE: f32 = 64.0;
+/// The evaluation lead that makes a cut-off playout a win about 73% of the
+/// time.
+const EVAL_SCALE: f32 = 256.0;
+/// How many of its best untried moves a node holds at a time; more are
+/// generated when they run out.
+const UNTRIED_BATCH: usize = 16;
+/// The part of the thinking time the threat search may use before the tree
+/// search, and that is kept back for checking the defence after it.
+const THREAT_TIME_SHARE: f64 = 0.25;
+
 #[derive(strum_macros::Display, Debug)]
 pub enum BotError {
     NoMoreMoves,
@@ -17,11 +49,6 @@ pub enum BotError {
 
 impl Error for BotError {}
 
-const EXPLORATION_PARAM: f32 = 1.414;
-const NEIGHBOUR_CHANCE: f32 = 0.8;
-const WIN_VALUE: f32 = 1.;
-const DRAW_VALUE: f32 = 0.6;
-
 #[derive(Clone)]
 pub struct Node {
     parent: Option<usize>,
@@ -30,7 +57,22 @@ pub struct Node {
     game_move: Option<i16>,
     wins: f32,
     visits: i32,
+    /// All-moves-as-first statistics: playouts through the parent in which
+    /// `player` played `game_move` at any later point
+    amaf_wins: f32,
+    amaf_visits: i32,
+    /// How promising the move looked before searching it, see
+    /// `Bot::score_untried`
+    prior: f32,
+    /// Sorted by prior, the best last. Only generated when needed and
+    /// `UNTRIED_BATCH` at a time, so most nodes hold few or none.
     untried_moves: Vec<i16>,
+    untried_priors: Vec<f32>,
+    /// The raw scores of all the moves, added up when they were first
+    /// scored, so later batches share a scale with the first; 0 until then
+    prior_total: f32,
+    /// Whether there are moves left beyond `untried_moves` and `children`
+    more_untried: bool,
     player: Player,
 }
 
@@ -41,34 +83,204 @@ impl Node {
             children: vec![],
             wins: 0.0,
             visits: 0,
+            amaf_wins: 0.0,
+            amaf_visits: 0,
+            prior: 0.0,
             untried_moves: vec![],
+            untried_priors: vec![],
+            prior_total: 0.0,
+            more_untried: true,
             game_move: None,
             player: p,
         }
     }
 
-    pub fn can_expand(&self) -> bool {
-        let max_children = 2.0 * (self.visits as f32).sqrt();
-        self.untried_moves.len() > 0 && (self.children.len() as f32) < max_children
+    /// Whether the node may get another child. Moves that haven't been
+    /// generated yet are assumed to exist.
+    pub fn can_expand(&self, widening: f32) -> bool {
+        let max_children = widening * (self.visits as f32).sqrt();
+        (self.more_untried || !self.untried_moves.is_empty())
+            && (self.children.len() as f32) < max_children
+    }
+
+    /// Puts back the move of a dropped child, keeping the untried moves
+    /// sorted by prior.
+    fn return_untried(&mut self, m: i16, prior: f32) {
+        let at = self.untried_priors.partition_point(|&p| p < prior);
+        self.untried_moves.insert(at, m);
+        self.untried_priors.insert(at, prior);
+    }
+}
+
+/// A move considered at the root of the last search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub game_move: i16,
+    pub visits: i32,
+    pub wins: f32,
+}
+
+impl Candidate {
+    pub fn win_rate(&self) -> f32 {
+        if self.visits == 0 {
+            return 0.0;
+        }
+
+        self.wins / self.visits as f32
     }
 }
 
 pub struct Bot {
     nodes: Vec<Node>,
-    thinking_time: Duration,
+    /// The position at `nodes[0]`, with the root player to move.
+    root: Option<Board>,
+    config: BotConfig,
     turn: usize,
+    iterations: usize,
+    /// Each player's clock when playing under time control, indexed by
+    /// `Player::index`; without one every move gets `thinking_time`.
+    clocks: [Option<Clock>; 2],
 }
 
 impl Bot {
+    #[allow(dead_code)]
     pub fn new(thinking_time: Duration) -> Self {
+        Bot::with_config(BotConfig::new().thinking_time(thinking_time))
+    }
+
+    pub fn with_config(config: BotConfig) -> Self {
         Self {
             nodes: vec![],
-            thinking_time: thinking_time,
+            root: None,
+            config,
             turn: 0,
+            iterations: 0,
+            clocks: [None; 2],
+        }
+    }
+
+    pub fn set_thinking_time(&mut self, thinking_time: Duration) {
+        self.config.thinking_time = thinking_time;
+    }
+
+    /// Plays under time control, with `clock` for each player the bot moves
+    /// for. `None` goes back to a fixed `thinking_time` per move.
+    pub fn set_clock(&mut self, clock: Option<Clock>) {
+        self.clocks = [clock; 2];
+    }
+
+    /// How long `player` may think about their move on `board`.
+    fn time_budget(&self, board: &Board, player: Player) -> Duration {
+        match &self.clocks[player.index()] {
+            Some(clock) => {
+                let cells = board.cells().len();
+                let stones = board.cells().iter().filter(|c| c.is_some()).count();
+                clock.allocate(stones, cells)
+            }
+            None => self.config.thinking_time,
+        }
+    }
+
+    /// Whether the most visited root child stays ahead however the
+    /// remaining iterations go, at the rate the search has run so far.
+    fn is_decided(&self, iterations: usize, elapsed: Duration, budget: Duration) -> bool {
+        let mut visits: Vec<i32> = self.nodes[0]
+            .children
+            .iter()
+            .map(|&c| self.nodes[c].visits)
+            .collect();
+        visits.sort_unstable_by(|a, b| b.cmp(a));
+
+        let (best, second) = match visits[..] {
+            [] => return false,
+            [best] => (best, 0),
+            [best, second, ..] => (best, second),
+        };
+        let rate = iterations as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        let iterations_left = rate * budget.saturating_sub(elapsed).as_secs_f64();
+
+        (best - second) as f64 > iterations_left
+    }
+
+    /// How many MCTS iterations the last search ran, 0 if it was decided
+    /// without searching.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// The root moves of the last search, most visited first.
+    pub fn candidates(&self) -> Vec<Candidate> {
+        let Some(root) = self.nodes.first() else {
+            return vec![];
+        };
+
+        let mut candidates: Vec<Candidate> = root
+            .children
+            .iter()
+            .map(|&c| Candidate {
+                game_move: self.nodes[c].game_move.expect("child should have move"),
+                visits: self.nodes[c].visits,
+                wins: self.nodes[c].wins,
+            })
+            .collect();
+
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.visits));
+        candidates
+    }
+
+    /// The tree of the last search, down to `max_depth` moves below the root
+    /// and without the subtrees of nodes with fewer than `min_visits`.
+    /// `None` if the last move was found without searching.
+    pub fn export_tree(&self, max_depth: usize, min_visits: i32) -> Option<TreeNode> {
+        let n = self.root.as_ref()?.n();
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        Some(self.export_node(0, None, n, max_depth, min_visits))
+    }
+
+    fn export_node(
+        &self,
+        node_index: usize,
+        uct: Option<f32>,
+        n: i16,
+        depth_left: usize,
+        min_visits: i32,
+    ) -> TreeNode {
+        let node = &self.nodes[node_index];
+        let mut children: Vec<usize> = node
+            .children
+            .iter()
+            .copied()
+            .filter(|&c| depth_left > 0 && self.nodes[c].visits >= min_visits)
+            .collect();
+        children.sort_by_key(|&c| std::cmp::Reverse(self.nodes[c].visits));
+
+        let visits_ln = (node.visits as f32).ln();
+        let visits_sqrt = (node.visits as f32).sqrt();
+        let game_move = node.game_move.filter(|_| node_index != 0);
+        TreeNode {
+            game_move,
+            coord: game_move.map(|m| Notation::Coord.format(m, n)),
+            player: node.player,
+            wins: node.wins,
+            visits: node.visits,
+            uct,
+            children: children
+                .into_iter()
+                .map(|c| {
+                    let uct = self.uct_value(c, visits_ln, visits_sqrt);
+                    let uct = uct.is_finite().then_some(uct);
+                    self.export_node(c, uct, n, depth_left - 1, min_visits)
+                })
+                .collect(),
         }
     }
 
-    fn uct_value(&self, node_index: usize, p_v_ln: f32) -> f32 {
+    /// UCT with a PUCT-style bias towards moves with a high prior, which
+    /// fades as the move collects visits of its own.
+    fn uct_value(&self, node_index: usize, p_v_ln: f32, p_v_sqrt: f32) -> f32 {
         unsafe {
             let current = self.nodes.get_unchecked(node_index);
             if current.visits == 0 {
@@ -76,9 +288,18 @@ impl Bot {
             }
 
             let visits = current.visits as f32;
-            let wins = current.wins;
+            let mut value = current.wins / visits;
+            if self.config.rave && current.amaf_visits > 0 {
+                // The RAVE schedule: lean on the AMAF value while the node has
+                // few visits of its own, and on its real value as they grow.
+                let beta = (RAVE_EQUIVALENCE / (3.0 * visits + RAVE_EQUIVALENCE)).sqrt();
+                let amaf_value = current.amaf_wins / current.amaf_visits as f32;
+                value = (1.0 - beta) * value + beta * amaf_value;
+            }
 
-            wins / visits + EXPLORATION_PARAM * (p_v_ln / visits).sqrt()
+            value
+                + self.config.exploration * (p_v_ln / visits).sqrt()
+                + self.config.prior_weight * current.prior * p_v_sqrt / (1.0 + visits)
         }
     }
 
@@ -86,13 +307,14 @@ impl Bot {
         unsafe {
             let current = self.nodes.get_unchecked(node_index);
             let current_visits_ln = (current.visits as f32).ln();
+            let current_visits_sqrt = (current.visits as f32).sqrt();
 
             current
                 .children
                 .iter()
                 .max_by(|&&a, &&b| {
-                    let u_a = self.uct_value(a, current_visits_ln);
-                    let u_b = self.uct_value(b, current_visits_ln);
+                    let u_a = self.uct_value(a, current_visits_ln, current_visits_sqrt);
+                    let u_b = self.uct_value(b, current_visits_ln, current_visits_sqrt);
 
                     u_a.partial_cmp(&u_b).unwrap_or(std::cmp::Ordering::Equal)
                 })
@@ -101,56 +323,98 @@ impl Bot {
         }
     }
 
+    /// Scores `node_index`'s untried moves for `player` and sorts them, the
+    /// most promising last. The priors of all the node's moves add up to 1:
+    /// immediate wins first, then blocks, then moves next to stones and,
+    /// with `pattern_priors`, the runs they make or break.
+    fn score_untried(&mut self, node_index: usize, board: &mut BotBoard, player: Player) {
+        let mut untried = std::mem::take(&mut self.nodes[node_index].untried_moves);
+        let mut scores: Vec<f32> = untried
+            .iter()
+            .map(|&m| {
+                let mut score = 1.0;
+                if board.is_tactical_move(m, player) {
+                    score += if board.board.completes_line(m, player) {
+                        WIN_PRIOR
+                    } else {
+                        BLOCK_PRIOR
+                    };
+                }
+                if board.has_neighbour(m) {
+                    score += self.config.neighbour_chance * NEIGHBOUR_PRIOR;
+                }
+                if self.config.pattern_priors {
+                    score += eval::score_move(&board.board, m, player) as f32 / PATTERN_PRIOR_SCALE;
+                }
+                score
+            })
+            .collect();
+
+        let node = &mut self.nodes[node_index];
+        if node.prior_total == 0.0 {
+            node.prior_total = scores.iter().sum();
+        }
+        let total = node.prior_total;
+        let mut order: Vec<usize> = (0..untried.len()).collect();
+        order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+        untried = order.iter().map(|&i| untried[i]).collect();
+        scores = order.iter().map(|&i| scores[i] / total).collect();
+
+        let node = &mut self.nodes[node_index];
+        node.untried_moves = untried;
+        node.untried_priors = scores;
+    }
+
+    /// Fills `node_index`'s untried moves with the best `UNTRIED_BATCH` of
+    /// the moves it has no child for yet.
+    fn generate_untried(&mut self, node_index: usize, board: &mut BotBoard, player: Player) {
+        let node = &self.nodes[node_index];
+        let mut moves = board.legal_moves();
+        moves.retain(|&m| {
+            !node
+                .children
+                .iter()
+                .any(|&c| self.nodes[c].game_move == Some(m))
+        });
+        self.nodes[node_index].untried_moves = moves;
+        self.score_untried(node_index, board, player);
+
+        let node = &mut self.nodes[node_index];
+        let len = node.untried_moves.len();
+        node.more_untried = len > UNTRIED_BATCH;
+        if node.more_untried {
+            node.untried_moves = node.untried_moves.split_off(len - UNTRIED_BATCH);
+            node.untried_priors = node.untried_priors.split_off(len - UNTRIED_BATCH);
+        }
+    }
+
+    /// Adds a child for the untried move with the highest prior, generating
+    /// or scoring the untried moves first if needed. `None` if the board
+    /// turns out to be full.
     fn expand(
         &mut self,
         node_index: usize,
         board: &mut BotBoard,
         player: Player,
-    ) -> (usize, Option<Player>) {
-        let mut idx = Some(random_range(0..self.nodes[node_index].untried_moves.len()));
-        let mut tactical_moves: Vec<i16> = vec![];
-        let mut neighbour_moves: Vec<i16> = vec![];
-
-        self.nodes[node_index].untried_moves.iter().for_each(|&m| {
-            if board.is_tactical_move(m, player) {
-                tactical_moves.push(m);
-                return;
-            }
-
-            if tactical_moves.is_empty() && board.has_neighbour(m) {
-                neighbour_moves.push(m);
-            }
-        });
-
-        if !tactical_moves.is_empty() {
-            let m = tactical_moves[random_range(0..tactical_moves.len())];
-            idx = Some(
-                self.nodes[node_index]
-                    .untried_moves
-                    .iter()
-                    .position(|&um| um == m)
-                    .expect("untried moves should have the tactical move"),
-            );
+    ) -> Option<(usize, Option<Player>)> {
+        let node = &self.nodes[node_index];
+        if node.untried_moves.is_empty() && node.more_untried {
+            self.generate_untried(node_index, board, player);
+        } else if node.untried_priors.len() != node.untried_moves.len() {
+            self.score_untried(node_index, board, player);
         }
 
-        if idx.is_none()
-            && rand::random_bool(NEIGHBOUR_CHANCE as f64)
-            && !neighbour_moves.is_empty()
-        {
-            let m = neighbour_moves[random_range(0..neighbour_moves.len())];
-            idx = Some(
-                self.nodes[node_index]
-                    .untried_moves
-                    .iter()
-                    .position(|&um| um == m)
-                    .expect("untried moves should have the tactical move"),
-            );
+        let node = &mut self.nodes[node_index];
+        let m = node.untried_moves.pop()?;
+        let prior = node
+            .untried_priors
+            .pop()
+            .expect("untried moves should have priors");
+        if node.untried_moves.is_empty() {
+            node.untried_moves = vec![];
+            node.untried_priors = vec![];
         }
 
-        let m = self.nodes[node_index]
-            .untried_moves
-            .swap_remove(idx.unwrap());
-
         let winner = board
             .board
             .apply_move(m, player)
@@ -159,59 +423,114 @@ impl Bot {
         let child_index = self.nodes.len();
         let child = Node {
             parent: Some(node_index),
-            game_move: Some(m.try_into().expect("game move should fit in i16")),
+            game_move: Some(m),
             children: vec![],
             wins: 0.,
             visits: 0,
-            untried_moves: board.legal_moves(),
+            amaf_wins: 0.,
+            amaf_visits: 0,
+            prior,
+            untried_moves: vec![],
+            untried_priors: vec![],
+            prior_total: 0.0,
+            more_untried: true,
             player: player,
         };
 
         self.nodes.push(child);
         self.nodes[node_index].children.push(child_index);
 
-        (child_index, winner)
+        Some((child_index, winner))
     }
 
-    fn rollout(&self, board: &mut BotBoard, mut player: Player) -> Option<Player> {
-        let mut moves = board.legal_moves();
+    /// Plays the game out from `board`, adding the moves to `played`.
+    fn rollout(
+        &self,
+        board: &mut BotBoard,
+        player: Player,
+        played: &mut Vec<(i16, Player)>,
+    ) -> Option<Player> {
+        if self.config.rollout != RolloutPolicy::Uniform || self.config.rollout_cutoff.is_some() {
+            return guided_play_out(
+                board,
+                player,
+                self.config.rollout,
+                self.config.rollout_cutoff,
+                played,
+            );
+        }
 
-        loop {
-            if moves.is_empty() {
-                return None;
-            }
+        let n = board.board.n();
+        if board.board.variant() != Variant::Freestyle {
+            return play_out(&mut board.board, player, played);
+        }
 
-            let idx = random_range(0..moves.len());
-            let winner = board
-                .board
-                .apply_move(moves[idx], player)
-                .expect("legal move");
+        if BitBoard::<u128>::fits(n) {
+            return play_out(&mut BitBoard::<u128>::from(&board.board), player, played);
+        }
 
-            if winner.is_some() {
-                return winner;
-            }
+        if BitBoard::<U256>::fits(n) {
+            return play_out(&mut BitBoard::<U256>::from(&board.board), player, played);
+        }
 
-            moves.swap_remove(idx);
+        play_out(&mut board.board, player, played)
+    }
 
-            player = player.next();
+    /// What a playout won by `winner` is worth to `player`.
+    fn value(&self, winner: Option<Player>, player: Player) -> f32 {
+        match winner {
+            Some(p) if p == player => self.config.win_value,
+            Some(_) => 0.0,
+            None => self.config.draw_value,
         }
     }
 
-    fn backpropagate(&mut self, mut node_index: usize, winner: Option<Player>) {
+    /// Credits the AMAF statistics of the siblings along the path from
+    /// `node_index`, at `depth`, up to the root: a child counts if its player
+    /// made its move anywhere after the parent in `played`, the moves of
+    /// this iteration starting from the root. `first_played` is scratch space
+    /// with a `None` per cell, and is left that way.
+    fn update_amaf(
+        &mut self,
+        mut node_index: usize,
+        mut depth: usize,
+        winner: Option<Player>,
+        played: &[(i16, Player)],
+        first_played: &mut [Option<(usize, Player)>],
+    ) {
+        for (i, &(m, p)) in played.iter().enumerate().rev() {
+            first_played[m as usize] = Some((i, p));
+        }
+
         loop {
-            self.nodes[node_index].visits += 1;
-            let val = match winner {
-                Some(p) => {
-                    if p == self.nodes[node_index].player {
-                        WIN_VALUE
-                    } else {
-                        0.0
-                    }
+            for i in 0..self.nodes[node_index].children.len() {
+                let c = self.nodes[node_index].children[i];
+                let m = self.nodes[c].game_move.expect("child should have move");
+                if let Some((i, p)) = first_played[m as usize]
+                    && i >= depth
+                    && p == self.nodes[c].player
+                {
+                    self.nodes[c].amaf_visits += 1;
+                    self.nodes[c].amaf_wins += self.value(winner, p);
                 }
-                None => DRAW_VALUE,
+            }
+
+            let Some(parent) = self.nodes[node_index].parent else {
+                break;
             };
+            node_index = parent;
+            depth -= 1;
+        }
 
-            self.nodes[node_index].wins += val;
+        for &(m, _) in played {
+            first_played[m as usize] = None;
+        }
+    }
+
+    fn backpropagate(&mut self, mut node_index: usize, winner: Option<Player>) {
+        loop {
+            self.nodes[node_index].visits += 1;
+            self.nodes[node_index].wins += self.value(winner, self.nodes[node_index].player);
 
             if self.nodes[node_index].parent.is_none() {
                 return;
@@ -226,53 +545,422 @@ impl Bot {
         original_board: &Board,
         player: Player,
     ) -> Result<i16, Box<dyn Error>> {
-        self.nodes.clear();
+        Ok(self.search_with(original_board, player, &StopHandle::new(), |_| {})?)
+    }
+
+    /// Searches like `find_next_move`, but ends early once `stop` is set and
+    /// reports progress to `on_progress` every `PROGRESS_INTERVAL`. It runs
+    /// until the root has at least one child, so there is a move to return.
+    /// The time taken is charged to `player`'s clock, if they have one.
+    pub fn search_with<F: FnMut(&Progress)>(
+        &mut self,
+        original_board: &Board,
+        player: Player,
+        stop: &StopHandle,
+        on_progress: F,
+    ) -> Result<i16, BotError> {
+        let started = Instant::now();
+        let result = self.timed_search(original_board, player, stop, on_progress);
+        if let Some(clock) = &mut self.clocks[player.index()] {
+            clock.spend(started.elapsed());
+            log::debug!("{player} has {:?} left", clock.remaining());
+        }
+
+        result
+    }
+
+    fn timed_search<F: FnMut(&Progress)>(
+        &mut self,
+        original_board: &Board,
+        player: Player,
+        stop: &StopHandle,
+        mut on_progress: F,
+    ) -> Result<i16, BotError> {
+        self.iterations = 0;
+        let started = Instant::now();
+        let budget = self.time_budget(original_board, player);
 
         let mut board = BotBoard::new(original_board.clone());
         let legal_moves = board.legal_moves();
         self.turn = original_board.cells().len() - legal_moves.len();
 
-        let (winning_move, blocking_moves) = board.terminating_moves(player);
+        let (winning_move, blocking_moves) = if self.config.tactical_shortcut {
+            board.terminating_moves(player)
+        } else {
+            (None, vec![])
+        };
+        let terminating_time = started.elapsed();
 
         if let Some(m) = winning_move {
+            log::debug!("{player} wins with {m}, found in {terminating_time:?}");
+            self.clear_tree();
             return Ok(m);
         }
 
         if blocking_moves.len() == 1 {
+            log::debug!(
+                "{player} is forced to block at {}, found in {terminating_time:?}",
+                blocking_moves[0]
+            );
+            self.clear_tree();
             return Ok(blocking_moves[0]);
         }
 
-        let mut root = Node::new(player);
-        root.untried_moves = legal_moves;
-        if root.untried_moves.is_empty() {
-            return Err(Box::from(BotError::NoMoreMoves));
+        let mut threat = None;
+        if self.config.tactical_shortcut {
+            let mut threats = ThreatSearch::new()
+                .with_node_limit(self.config.threat_nodes)
+                .with_deadline(started + budget.mul_f64(THREAT_TIME_SHARE));
+            if let Some(m) = threats.find_win(original_board, player) {
+                log::debug!(
+                    "{player} has a forced win starting with {m}, found in {:?} and {} nodes",
+                    started.elapsed(),
+                    threats.nodes()
+                );
+                self.clear_tree();
+                return Ok(m);
+            }
+
+            threat = threats.find_threat(original_board, player);
+            if let Some(m) = threat {
+                log::debug!("{} threatens a forced win starting with {m}", player.next());
+            }
+        }
+        let terminating_time = started.elapsed();
+        let search_budget = if threat.is_some() {
+            budget.mul_f64(1.0 - THREAT_TIME_SHARE)
+        } else {
+            budget
+        };
+
+        self.prepare_root(original_board, player, &legal_moves)?;
+
+        // With a temperature the move is sampled from all the visit counts, so
+        // the search can't end as soon as the best move is settled.
+        let stop_when_decided = self.config.temperature <= 0.0;
+        let mut last_report = Instant::now();
+        let (iterations, max_depth) =
+            self.search(original_board, player, &mut board, |bot, iterations| {
+                if bot.nodes[0].children.is_empty() {
+                    return false;
+                }
+
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    if let Some(progress) = bot.progress(iterations, started.elapsed()) {
+                        on_progress(&progress);
+                    }
+                }
+
+                let elapsed = started.elapsed();
+                if stop_when_decided
+                    && iterations > 0
+                    && iterations % DECIDED_CHECK_INTERVAL == 0
+                    && bot.is_decided(iterations, elapsed, search_budget)
+                {
+                    log::debug!("search decided after {iterations} iterations in {elapsed:?}");
+                    return true;
+                }
+
+                stop.is_stopped() || elapsed > search_budget
+            });
+
+        let best_node = self.choose_root_child();
+
+        let mut best_move = self.nodes[best_node]
+            .game_move
+            .expect("node should have move");
+
+        if let Some(defence) = threat
+            && best_move != defence
+        {
+            let mut after = original_board.clone();
+            after
+                .apply_move(best_move, player)
+                .expect("best move should be legal");
+            if ThreatSearch::new()
+                .with_node_limit(self.config.threat_nodes)
+                .with_deadline(started + budget)
+                .find_win(&after, player.next())
+                .is_some()
+            {
+                log::debug!("{best_move} does not stop the threat, playing {defence}");
+                best_move = defence;
+            }
+        }
+
+        if !blocking_moves.is_empty() && !blocking_moves.contains(&best_move) {
+            best_move = *blocking_moves
+                .choose(&mut rng())
+                .expect("blocking moves should not be empty");
         }
 
-        root.game_move = Some(
-            root.untried_moves[0]
-                .try_into()
-                .expect("game move should fit in i16"),
+        self.iterations = iterations;
+        self.trace_search(
+            terminating_time,
+            started.elapsed() - terminating_time,
+            max_depth,
         );
-        self.nodes.push(root);
+        Ok(best_move)
+    }
+
+    /// Keeps searching `board` for `player` on a worker thread, typically
+    /// from the opponent's point of view while they think. Once stopped, the
+    /// next `find_next_move` picks up the subtree of whatever move was played.
+    pub fn ponder(mut self, board: &Board, player: Player) -> Ponder {
+        let stop = StopHandle::new();
+        let stop_flag = stop.clone();
+        let original_board = board.clone();
+
+        let handle = thread::spawn(move || {
+            let mut board = BotBoard::new(original_board.clone());
+            let legal_moves = board.legal_moves();
+            if !original_board.winning_line().is_empty()
+                || self
+                    .prepare_root(&original_board, player, &legal_moves)
+                    .is_err()
+            {
+                return self;
+            }
+
+            let (iterations, max_depth) =
+                self.search(&original_board, player, &mut board, |_, _| {
+                    stop_flag.is_stopped()
+                });
+            log::debug!(
+                "pondered {iterations} iterations for {player}, {} nodes, depth {max_depth}",
+                self.nodes.len()
+            );
+            self
+        });
+
+        Ponder::new(stop, handle)
+    }
+
+    /// Runs `search_with` on a worker thread, handing the bot back with the
+    /// result when the search ends or is stopped.
+    pub fn start_search(mut self, board: &Board, player: Player) -> Search {
+        let stop = StopHandle::new();
+        let stop_flag = stop.clone();
+        let (sender, progress) = mpsc::channel();
+        let board = board.clone();
+
+        let handle = thread::spawn(move || {
+            let m = self.search_with(&board, player, &stop_flag, |p| {
+                let _ = sender.send(p.clone());
+            });
+            (self, m)
+        });
+
+        Search::new(stop, progress, handle)
+    }
+
+    fn progress(&self, iterations: usize, elapsed: Duration) -> Option<Progress> {
+        let &best = self
+            .nodes
+            .first()?
+            .children
+            .iter()
+            .max_by_key(|&&c| self.nodes[c].visits)?;
+
+        Some(Progress {
+            best_move: self.nodes[best].game_move?,
+            visits: self.nodes[best].visits,
+            iterations,
+            elapsed,
+        })
+    }
+
+    /// Reuses the tree when possible, otherwise starts a new one for `board`.
+    fn prepare_root(
+        &mut self,
+        board: &Board,
+        player: Player,
+        legal_moves: &[i16],
+    ) -> Result<(), BotError> {
+        if !self.reuse_tree(board, player) {
+            let mut root = Node::new(player);
+            root.untried_moves = symmetry::unique_moves(board, legal_moves);
+            root.more_untried = false;
+            root.game_move = root.untried_moves.first().copied();
+
+            let mut position = board.clone();
+            position.set_to_move(player);
+            self.nodes.clear();
+            self.nodes.push(root);
+            self.root = Some(position);
+        }
+
+        // The root holds all of its moves, a reused leaf may not yet.
+        let root = &mut self.nodes[0];
+        if root.more_untried && root.children.is_empty() {
+            root.untried_moves = symmetry::unique_moves(board, legal_moves);
+            root.untried_priors = vec![];
+            root.prior_total = 0.0;
+            root.more_untried = false;
+        }
+
+        if root.untried_moves.is_empty() && root.children.is_empty() {
+            return Err(BotError::NoMoreMoves);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the tree can carry on from `board`: either it is the root
+    /// position, or it follows from it by one or two moves the tree has
+    /// already explored, in which case that node becomes the new root.
+    fn reuse_tree(&mut self, board: &Board, player: Player) -> bool {
+        let Some(root) = &self.root else {
+            return false;
+        };
+        if self.nodes.is_empty()
+            || root.cells().len() != board.cells().len()
+            || root.k() != board.k()
+            || root.variant() != board.variant()
+        {
+            return false;
+        }
+
+        let mut added = vec![];
+        for (m, (&before, &after)) in root.cells().iter().zip(board.cells()).enumerate() {
+            match (before, after) {
+                _ if before == after => {}
+                (None, Some(p)) => added.push((m as i16, p)),
+                _ => return false,
+            }
+        }
+
+        let mut to_move = root.to_move();
+        if added.len() > 2 {
+            return false;
+        }
+        added.sort_by_key(|&(_, p)| p != to_move);
+
+        let mut node_index = 0;
+        for (m, p) in added {
+            let child = self.nodes[node_index]
+                .children
+                .iter()
+                .find(|&&c| self.nodes[c].game_move == Some(m));
+            match child {
+                Some(&c) if p == to_move => node_index = c,
+                _ => return false,
+            }
+            to_move = to_move.next();
+        }
+        if to_move != player {
+            return false;
+        }
+
+        self.reroot(node_index);
+        let mut position = board.clone();
+        position.set_to_move(player);
+        self.root = Some(position);
+        true
+    }
+
+    /// Forgets the tree, for moves played without searching, so it isn't
+    /// reported or exported as if it belonged to them.
+    fn clear_tree(&mut self) {
+        self.nodes.clear();
+        self.root = None;
+    }
+
+    /// Drops everything outside the subtree of `new_root` and compacts what
+    /// is left, with `new_root` at index 0.
+    fn reroot(&mut self, new_root: usize) {
+        if new_root == 0 {
+            return;
+        }
+
+        self.compact(new_root, 0);
+    }
+
+    /// Makes room once the tree holds `max_nodes`: keeps at most half of
+    /// them, the most visited. A child never has more visits than its
+    /// parent, so what is kept is still a tree.
+    fn collect_garbage(&mut self) {
+        let keep = self.config.max_nodes / 2;
+        let mut visits: Vec<i32> = self.nodes[1..].iter().map(|n| n.visits).collect();
+        if keep >= visits.len() {
+            return;
+        }
 
+        let (_, &mut cutoff, _) = visits.select_nth_unstable_by(keep, |a, b| b.cmp(a));
+        let before = self.nodes.len();
+        self.compact(0, cutoff + 1);
+        log::debug!(
+            "collected {} of {before} nodes with {cutoff} visits or fewer",
+            before - self.nodes.len()
+        );
+    }
+
+    /// Rebuilds the tree from the subtree of `new_root`, which ends up at
+    /// index 0, dropping every subtree whose root has fewer than
+    /// `min_visits`. The moves of dropped children go back to their parents'
+    /// untried moves, so they can be searched again.
+    fn compact(&mut self, new_root: usize, min_visits: i32) {
+        let mut old = std::mem::take(&mut self.nodes);
+        let mut stack = vec![(new_root, None)];
+        while let Some((old_index, parent)) = stack.pop() {
+            let index = self.nodes.len();
+            let mut node = std::mem::replace(&mut old[old_index], Node::new(Player::X));
+            let children = std::mem::take(&mut node.children);
+            node.parent = parent;
+            self.nodes.push(node);
+
+            if let Some(parent) = parent {
+                self.nodes[parent].children.push(index);
+            }
+            for &c in children.iter().rev() {
+                if old[c].visits >= min_visits {
+                    stack.push((c, Some(index)));
+                } else {
+                    let m = old[c].game_move.expect("child should have move");
+                    self.nodes[index].return_untried(m, old[c].prior);
+                }
+            }
+        }
+    }
+
+    /// Runs MCTS iterations from the root until `stop`, given the bot and
+    /// the iterations so far, says otherwise. Returns the iterations run and
+    /// the deepest node reached.
+    fn search<F: FnMut(&Bot, usize) -> bool>(
+        &mut self,
+        original_board: &Board,
+        player: Player,
+        board: &mut BotBoard,
+        mut stop: F,
+    ) -> (usize, usize) {
         let mut iterations = 0;
-        let now = SystemTime::now();
+        let mut max_depth = 0;
+        let mut played = vec![];
+        let mut first_played = vec![None; original_board.cells().len()];
         'iter_loop: loop {
-            if now.elapsed().expect("time working") > self.thinking_time {
+            if stop(self, iterations) {
                 break 'iter_loop;
             };
             iterations += 1;
 
+            if self.nodes.len() >= self.config.max_nodes {
+                self.collect_garbage();
+            }
+
             board.update_board(original_board.clone());
+            played.clear();
 
             let mut current_player = player;
             let mut current_node_index = 0;
+            let mut depth = 0;
 
             //SELECTION
-            while !self.nodes[current_node_index].can_expand()
+            while !self.nodes[current_node_index].can_expand(self.config.widening)
                 && !self.nodes[current_node_index].children.is_empty()
             {
                 current_node_index = self.select_child(current_node_index);
+                depth += 1;
 
                 let game_move = self.nodes[current_node_index]
                     .game_move
@@ -280,14 +968,22 @@ impl Bot {
 
                 let winner = board
                     .board
-                    .apply_move(
-                        game_move.try_into().expect("game move should fit in i16"),
-                        current_player,
-                    )
+                    .apply_move(game_move, current_player)
                     .expect("valid move");
+                played.push((game_move, current_player));
 
                 if winner.is_some() {
+                    max_depth = max_depth.max(depth);
                     self.backpropagate(current_node_index, winner);
+                    if self.config.rave {
+                        self.update_amaf(
+                            current_node_index,
+                            depth,
+                            winner,
+                            &played,
+                            &mut first_played,
+                        );
+                    }
                     continue 'iter_loop;
                 }
 
@@ -295,43 +991,181 @@ impl Bot {
             }
 
             //EXPANSION
-            if self.nodes[current_node_index].can_expand() {
-                let (new_node_index, winner) =
-                    self.expand(current_node_index, &mut board, current_player);
+            if self.nodes[current_node_index].can_expand(self.config.widening)
+                && let Some((new_node_index, winner)) =
+                    self.expand(current_node_index, board, current_player)
+            {
+                let game_move = self.nodes[new_node_index].game_move;
+                played.push((game_move.expect("node with move"), current_player));
 
                 if winner.is_some() {
                     self.backpropagate(current_node_index, winner);
+                    if self.config.rave {
+                        self.update_amaf(
+                            current_node_index,
+                            depth,
+                            winner,
+                            &played,
+                            &mut first_played,
+                        );
+                    }
                     continue 'iter_loop;
                 }
 
                 current_node_index = new_node_index;
                 current_player = current_player.next();
+                depth += 1;
             }
+            max_depth = max_depth.max(depth);
 
             //SIMULATION
-            let winner = self.rollout(&mut board, current_player);
+            let winner = self.rollout(board, current_player, &mut played);
 
             //BACKPROPAGATION
             self.backpropagate(current_node_index, winner);
+            if self.config.rave {
+                self.update_amaf(
+                    current_node_index,
+                    depth,
+                    winner,
+                    &played,
+                    &mut first_played,
+                );
+            }
         }
 
-        let &best_node = self.nodes[0]
-            .children
+        (iterations, max_depth)
+    }
+
+    /// The most visited root child, or with a temperature a child sampled by
+    /// a softmax over the visit counts: with probability proportional to
+    /// `exp(visits / (max_visits * temperature))`, so the temperature is in
+    /// units of the most visited child's count.
+    fn choose_root_child(&self) -> usize {
+        let children = &self.nodes[0].children;
+        let &most_visited = children
             .iter()
             .max_by(|&&a, &&b| self.nodes[a].visits.cmp(&self.nodes[b].visits))
             .expect("nodes should not be empty");
 
-        let mut best_move = self.nodes[best_node]
-            .game_move
-            .expect("node should have move");
+        let temperature = self.config.temperature as f64;
+        let max_visits = self.nodes[most_visited].visits as f64;
+        if temperature <= 0.0 || max_visits == 0.0 {
+            return most_visited;
+        }
 
-        if !blocking_moves.is_empty() && !blocking_moves.contains(&best_move) {
-            best_move = *blocking_moves
-                .choose(&mut rng())
-                .expect("blocking moves should not be empty");
+        // Shifted by the largest logit, 1 / temperature, so nothing overflows.
+        let weights: Vec<f64> = children
+            .iter()
+            .map(|&c| ((self.nodes[c].visits as f64 / max_visits - 1.0) / temperature).exp())
+            .collect();
+        let mut r = random_range(0.0..weights.iter().sum::<f64>());
+        for (&c, w) in children.iter().zip(weights) {
+            if r < w {
+                return c;
+            }
+            r -= w;
         }
 
-        println!("iterations: {iterations}");
-        Ok(best_move)
+        most_visited
+    }
+
+    fn trace_search(&self, terminating_time: Duration, search_time: Duration, max_depth: usize) {
+        if !log::log_enabled!(log::Level::Debug) {
+            return;
+        }
+
+        let rate = self.iterations as f64 / search_time.as_secs_f64();
+        log::debug!(
+            "{} iterations in {search_time:?} ({rate:.0}/s), {} nodes, depth {max_depth}, \
+             terminating moves took {terminating_time:?}",
+            self.iterations,
+            self.nodes.len(),
+        );
+
+        for c in self.candidates().iter().take(5) {
+            log::trace!(
+                "candidate {}: {} visits, {:.1}%",
+                c.game_move,
+                c.visits,
+                100.0 * c.win_rate()
+            );
+        }
+    }
+}
+
+/// A playout on the full board, so the policy can look at lines and
+/// neighbours.
+fn guided_play_out(
+    board: &mut BotBoard,
+    mut player: Player,
+    policy: RolloutPolicy,
+    cutoff: Option<usize>,
+    played: &mut Vec<(i16, Player)>,
+) -> Option<Player> {
+    let mut moves = board.legal_moves();
+    let start = played.len();
+
+    loop {
+        if moves.is_empty() {
+            return None;
+        }
+
+        if cutoff.is_some_and(|plies| played.len() - start >= plies) {
+            return evaluated_winner(&board.board, player);
+        }
+
+        let idx = policy.choose(board, &moves, player);
+        let winner = board
+            .board
+            .apply_move(moves[idx], player)
+            .expect("legal move");
+        played.push((moves[idx], player));
+
+        if winner.is_some() {
+            return winner;
+        }
+
+        moves.swap_remove(idx);
+
+        player = player.next();
+    }
+}
+
+/// A winner drawn for a playout cut short, more likely the further the
+/// evaluation favours them.
+fn evaluated_winner(board: &Board, player: Player) -> Option<Player> {
+    let score = eval::evaluate(board, player) as f32;
+    let win_chance = 1.0 / (1.0 + (-score / EVAL_SCALE).exp());
+    if rand::random_bool(win_chance as f64) {
+        Some(player)
+    } else {
+        Some(player.next())
+    }
+}
+
+fn play_out<B: GameBoard>(
+    board: &mut B,
+    mut player: Player,
+    played: &mut Vec<(i16, Player)>,
+) -> Option<Player> {
+    let mut moves = board.legal_moves();
+
+    loop {
+        if moves.is_empty() {
+            return None;
+        }
+
+        let idx = random_range(0..moves.len());
+        let winner = board.apply_move(moves[idx], player).expect("legal move");
+        played.push((moves[idx], player));
+
+        if winner.is_some() {
+            return winner;
+        }
+
+        moves.swap_remove(idx);
+
+        player = player.next();
     }
 }
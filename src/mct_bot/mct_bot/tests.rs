@@ -7,6 +7,7 @@ use crate::{
 
 use crate::mct_bot::mct_bot::BotBoard;
 use crate::mct_bot::mct_bot::Node;
+use crate::mct_bot::mct_bot::UNTRIED_BATCH;
 
 const TEST_THINKING_TIME: Duration = Duration::new(5, 0);
 
@@ -24,7 +25,7 @@ mod tests {
         [ ][ ][ ][X][O]
         [ ][ ][ ][ ][ ]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut test_board = Board::new(n as i16, 3);
         for (m, p) in cells.iter().enumerate() {
             if p.is_none() {
@@ -46,14 +47,15 @@ mod tests {
 
     #[test]
     fn find_best_move_block() {
+        // O wins at 1 unless X takes it first; X has no win of its own.
         let board = "
         [ ][ ][ ][ ][ ]
         [ ][X][O][ ][ ]
-        [ ][ ][X][O][ ]
-        [ ][X][ ][ ][O]
+        [ ][ ][ ][O][ ]
+        [ ][ ][ ][ ][X]
         [ ][ ][ ][ ][ ]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut test_board = Board::new(n as i16, 3);
         for (m, p) in cells.iter().enumerate() {
             if p.is_none() {
@@ -75,6 +77,8 @@ mod tests {
 
     #[test]
     fn find_best_move_block_open_three() {
+        // O's open three on the diagonal makes an open four next unless X
+        // takes an end of it.
         let board = "
         [ ][ ][ ][ ][ ][ ][ ][ ][ ]
         [ ][ ][ ][ ][ ][ ][ ][ ][ ]
@@ -86,8 +90,8 @@ mod tests {
         [ ][ ][ ][ ][ ][ ][ ][ ][ ]
         [ ][ ][ ][ ][ ][ ][ ][ ][ ]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
-        let mut test_board = Board::new(n as i16, 3);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
+        let mut test_board = Board::new(n as i16, 4);
         for (m, p) in cells.iter().enumerate() {
             if p.is_none() {
                 continue;
@@ -103,7 +107,6 @@ mod tests {
             .find_next_move(&test_board, crate::board::Player::X)
             .expect("could find move");
 
-        println!("FOUND {m}");
         assert!(m == 20 || m == 60);
     }
 
@@ -116,7 +119,7 @@ mod tests {
         [ ][X][ ][ ][O]
         [ ][ ][ ][ ][ ]
         ";
-        let (n, cells) = board::from_board_string_to_state(board);
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
         let mut raw_test_board = Board::new(n as i16, 3);
         let mut test_board = BotBoard::new(raw_test_board.clone());
         for (m, p) in cells.iter().enumerate() {
@@ -134,10 +137,420 @@ mod tests {
         let mut root = Node::new(Player::X);
 
         root.untried_moves = test_board.legal_moves();
+        root.more_untried = false;
+        b.nodes.push(root);
+
+        let (n_i, winner) = b
+            .expand(0, &mut test_board, Player::X)
+            .expect("root should have untried moves");
+
+        // X can win at once at 0, 8, 11, 18 or 20, and a win's prior beats
+        // the block at 1, so expansion takes a win first: the one with the
+        // highest prior, ties going to the last move generated.
+        assert_eq!(b.nodes[n_i].game_move, Some(20));
+        assert_eq!(winner, Some(Player::X));
+        let root = &b.nodes[0];
+        assert!(root.untried_priors.iter().all(|&p| p <= b.nodes[n_i].prior));
+        assert_eq!(
+            root.untried_moves[root.untried_moves.len() - 5..],
+            [1, 0, 8, 11, 18]
+        );
+    }
+
+    #[test]
+    fn widening_limits_children() {
+        let mut node = Node::new(Player::X);
+        node.visits = 4;
+        node.children = vec![1, 2, 3];
+        node.untried_moves = vec![4];
+        node.more_untried = false;
+
+        assert!(node.can_expand(2.0));
+        assert!(!node.can_expand(1.0));
+    }
+
+    #[test]
+    fn config_bot_finds_win() {
+        let mut test_board = Board::new(3, 3);
+        for (m, p) in [
+            (0, Player::X),
+            (3, Player::O),
+            (1, Player::X),
+            (4, Player::O),
+        ] {
+            test_board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let config = mct_bot::BotConfig::new()
+            .thinking_time(Duration::from_millis(50))
+            .exploration(0.5)
+            .widening(1.0)
+            .draw_value(0.5);
+        let mut b = mct_bot::Bot::with_config(config);
+
+        let m = b
+            .find_next_move(&test_board, Player::X)
+            .expect("bot should find a move");
+        assert_eq!(m, 2);
+    }
+
+    #[test]
+    fn easy_bot_plays_legal_moves() {
+        let mut test_board = Board::new(3, 3);
+        test_board
+            .apply_move(4, Player::X)
+            .expect("move should be valid");
+
+        let mut b = mct_bot::Bot::with_config(mct_bot::Difficulty::Easy.config());
+        let m = b
+            .find_next_move(&test_board, Player::O)
+            .expect("bot should find a move");
+        assert!(test_board.cells()[m as usize].is_none());
+    }
+
+    #[test]
+    fn ponder_keeps_subtree() {
+        let mut test_board = Board::new(5, 4);
+        // Without the shortcut, which can find a forced win here and play it
+        // without a tree to reuse.
+        let config = mct_bot::BotConfig::new()
+            .thinking_time(Duration::from_millis(100))
+            .tactical_shortcut(false);
+        let mut b = mct_bot::Bot::with_config(config);
+
+        let m = b
+            .find_next_move(&test_board, Player::X)
+            .expect("bot should find a move");
+        test_board
+            .apply_move(m, Player::X)
+            .expect("move should be valid");
+
+        let ponder = b.ponder(&test_board, Player::O);
+        std::thread::sleep(Duration::from_millis(100));
+        let mut b = ponder.stop();
+
+        let reply = b.candidates()[0].game_move;
+        test_board
+            .apply_move(reply, Player::O)
+            .expect("move should be valid");
+        let pondered_visits = b.nodes[b.nodes[0].children[0]].visits;
+        assert!(pondered_visits > 0);
+
+        b.find_next_move(&test_board, Player::X)
+            .expect("bot should find a move");
+        assert!(b.nodes[0].visits as usize > b.iterations());
+        assert_eq!(b.nodes[0].parent, None);
+        assert!(b.nodes.iter().skip(1).all(|n| n.parent.is_some()));
+    }
+
+    #[test]
+    fn unrelated_position_drops_tree() {
+        let mut b = mct_bot::Bot::new(Duration::from_millis(50));
+        b.find_next_move(&Board::new(4, 3), Player::X)
+            .expect("bot should find a move");
+
+        let mut other = Board::new(4, 3);
+        other
+            .apply_move(0, Player::O)
+            .expect("move should be valid");
+        b.find_next_move(&other, Player::X)
+            .expect("bot should find a move");
+
+        assert_eq!(b.nodes[0].visits as usize, b.iterations());
+    }
+
+    #[test]
+    fn tactical_rollout_takes_wins() {
+        let mut test_board = Board::new(7, 4);
+        for (m, p) in [
+            (0, Player::X),
+            (14, Player::O),
+            (1, Player::X),
+            (15, Player::O),
+            (2, Player::X),
+            (16, Player::O),
+        ] {
+            test_board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let bot = mct_bot::Bot::with_config(
+            mct_bot::BotConfig::new().rollout(mct_bot::RolloutPolicy::Tactical),
+        );
+        let mut board = BotBoard::new(test_board.clone());
+        for player in [Player::X, Player::O] {
+            for _ in 0..20 {
+                board.update_board(test_board.clone());
+                assert_eq!(bot.rollout(&mut board, player, &mut vec![]), Some(player));
+            }
+        }
+    }
+
+    #[test]
+    fn rave_bot_finds_win() {
+        let mut test_board = Board::new(3, 3);
+        for (m, p) in [
+            (0, Player::X),
+            (3, Player::O),
+            (1, Player::X),
+            (4, Player::O),
+        ] {
+            test_board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let config = mct_bot::BotConfig::new()
+            .thinking_time(Duration::from_millis(50))
+            .tactical_shortcut(false)
+            .rave(true);
+        let mut b = mct_bot::Bot::with_config(config);
+
+        let m = b
+            .find_next_move(&test_board, Player::X)
+            .expect("bot should find a move");
+        assert_eq!(m, 2);
+        assert!(b.nodes[b.nodes[0].children[0]].amaf_visits > 0);
+    }
+
+    #[test]
+    fn pattern_bot_blocks() {
+        let mut test_board = Board::new(9, 4);
+        for (m, p) in [
+            (40, Player::X),
+            (0, Player::O),
+            (41, Player::X),
+            (8, Player::O),
+            (42, Player::X),
+        ] {
+            test_board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let config = mct_bot::BotConfig::new()
+            .thinking_time(Duration::from_millis(200))
+            .tactical_shortcut(false)
+            .rollout_cutoff(Some(4))
+            .pattern_priors(true);
+        let mut b = mct_bot::Bot::with_config(config);
+
+        let m = b
+            .find_next_move(&test_board, Player::O)
+            .expect("bot should find a move");
+        assert!(m == 39 || m == 43);
+    }
+
+    #[test]
+    fn temperature_samples_by_softmax() {
+        let sample = |temperature: f32| {
+            let config = mct_bot::BotConfig::new().temperature(temperature);
+            let mut b = mct_bot::Bot::with_config(config);
+            b.nodes.push(Node::new(Player::X));
+            for (m, visits) in [(0, 100), (1, 50), (2, 0)] {
+                let mut child = Node::new(Player::X);
+                child.game_move = Some(m);
+                child.visits = visits;
+                child.parent = Some(0);
+                let index = b.nodes.len();
+                b.nodes[0].children.push(index);
+                b.nodes.push(child);
+            }
+
+            let mut counts = [0; 3];
+            for _ in 0..1000 {
+                counts[b.choose_root_child() - 1] += 1;
+            }
+            counts
+        };
+
+        assert_eq!(sample(0.0), [1000, 0, 0]);
+        // Weights 1, e^-5 and e^-10.
+        assert!(sample(0.1)[0] > 950);
+        // Weights 1, e^-0.5 and e^-1: 0.50, 0.30 and 0.18.
+        let counts = sample(1.0);
+        assert!(counts[0] > counts[1] && counts[1] > counts[2] && counts[2] > 100);
+    }
+
+    #[test]
+    fn node_limit_keeps_most_visited() {
+        let mut test_board = Board::new(9, 4);
+        for (m, p) in [(40, Player::X), (41, Player::O), (31, Player::X)] {
+            test_board.apply_move(m, p).expect("move should be valid");
+        }
+
+        let config = mct_bot::BotConfig::new()
+            .thinking_time(Duration::from_millis(200))
+            .tactical_shortcut(false)
+            .max_nodes(500);
+        let mut b = mct_bot::Bot::with_config(config);
+        b.find_next_move(&test_board, Player::O)
+            .expect("bot should find a move");
+
+        assert!(b.nodes.len() <= 500);
+        assert!(b.iterations() > 500);
+        for (i, node) in b.nodes.iter().enumerate() {
+            let child_visits: i32 = node.children.iter().map(|&c| b.nodes[c].visits).sum();
+            assert!(child_visits <= node.visits);
+            for &c in &node.children {
+                assert_eq!(b.nodes[c].parent, Some(i));
+            }
+            assert_eq!(node.untried_moves.len(), node.untried_priors.len());
+        }
+    }
+
+    #[test]
+    fn export_tree_respects_limits() {
+        let mut b = mct_bot::Bot::new(Duration::from_millis(50));
+        assert!(b.export_tree(2, 1).is_none());
+        b.find_next_move(&Board::new(5, 4), Player::X)
+            .expect("bot should find a move");
+
+        let tree = b.export_tree(2, 5).expect("bot should have searched");
+        assert_eq!(tree.game_move, None);
+        assert_eq!(tree.visits, b.nodes[0].visits);
+        assert!(!tree.children.is_empty());
+        for (child, next) in tree.children.iter().zip(&tree.children[1..]) {
+            assert!(child.visits >= next.visits);
+        }
+        for child in &tree.children {
+            assert!(child.visits >= 5);
+            assert!(child.uct.is_some());
+            for grandchild in &child.children {
+                assert!(grandchild.children.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn leaves_generate_moves_lazily() {
+        let mut b = mct_bot::Bot::new(Duration::from_millis(50));
+        b.find_next_move(&Board::new(9, 4), Player::X)
+            .expect("bot should find a move");
+
+        let leaves: Vec<&Node> = b.nodes.iter().filter(|n| n.children.is_empty()).collect();
+        assert!(!leaves.is_empty());
+        assert!(leaves.iter().all(|n| n.untried_moves.is_empty()));
+        assert!(
+            b.nodes[1..]
+                .iter()
+                .all(|n| n.untried_moves.len() <= UNTRIED_BATCH)
+        );
+    }
+
+    /// Expands the root, which has no moves generated yet, until it has
+    /// `count` children, undoing each move again.
+    fn expand_root(b: &mut mct_bot::Bot, board: Board, player: Player, count: usize) {
+        let mut test_board = BotBoard::new(board);
+        b.nodes.push(Node::new(player));
+        for _ in 0..count {
+            let (child, _) = b
+                .expand(0, &mut test_board, player)
+                .expect("root should have untried moves");
+            let m = b.nodes[child].game_move.expect("child should have move");
+            test_board.board.undo_move(m).expect("undo should be valid");
+        }
+    }
+
+    #[test]
+    fn wins_and_blocks_are_expanded_first() {
+        // X wins at 2, O would win at 12.
+        let board = Board::from_moves(
+            5,
+            3,
+            &[
+                (0, Player::X),
+                (10, Player::O),
+                (1, Player::X),
+                (11, Player::O),
+            ],
+        );
+        let mut b = mct_bot::Bot::new(TEST_THINKING_TIME);
+        expand_root(&mut b, board, Player::X, 2);
+
+        let root = &b.nodes[0];
+        let children: Vec<&Node> = root.children.iter().map(|&c| &b.nodes[c]).collect();
+        assert_eq!(children[0].game_move, Some(2));
+        assert_eq!(children[1].game_move, Some(12));
+        assert!(children[0].prior > children[1].prior);
+        assert!(root.untried_priors.iter().all(|&p| p < children[1].prior));
+        assert!(root.untried_priors.is_sorted());
+        assert_eq!(root.untried_moves.len(), root.untried_priors.len());
+    }
+
+    #[test]
+    fn priors_share_one_scale_across_batches() {
+        let board = Board::from_moves(9, 4, &[(40, Player::X), (41, Player::O)]);
+        let config = mct_bot::BotConfig::new().pattern_priors(true);
+        let mut b = mct_bot::Bot::with_config(config);
+        expand_root(&mut b, board, Player::X, 79);
+
+        let root = &b.nodes[0];
+        assert!(root.untried_moves.is_empty() && !root.more_untried);
+        let priors: Vec<f32> = root.children.iter().map(|&c| b.nodes[c].prior).collect();
+        assert!(priors.windows(2).all(|w| w[0] >= w[1]));
+        let total: f32 = priors.iter().sum();
+        assert!((total - 1.0).abs() < 1e-4, "priors add up to {total}");
+    }
+
+    #[test]
+    fn prior_biases_selection() {
+        let mut b = mct_bot::Bot::new(TEST_THINKING_TIME);
+        let mut root = Node::new(Player::X);
+        root.visits = 100;
+        root.children = vec![1, 2];
         b.nodes.push(root);
+        for (m, prior) in [(0, 0.6), (1, 0.1)] {
+            let mut child = Node::new(Player::X);
+            child.parent = Some(0);
+            child.game_move = Some(m);
+            child.wins = 25.0;
+            child.visits = 50;
+            child.prior = prior;
+            b.nodes.push(child);
+        }
 
-        let (n_i, _) = b.expand(0, &mut test_board, Player::X);
+        let ln = 100f32.ln();
+        let bonus = b.uct_value(1, ln, 10.0) - b.uct_value(2, ln, 10.0);
+        assert!((bonus - 0.5 * 10.0 / 51.0).abs() < 1e-5);
+        assert_eq!(b.select_child(0), 1);
 
-        assert!(b.nodes[n_i].game_move == Some(1))
+        b.config = b.config.prior_weight(0.0);
+        assert_eq!(b.uct_value(1, ln, 10.0), b.uct_value(2, ln, 10.0));
+    }
+
+    #[test]
+    fn shortcut_moves_clear_the_tree() {
+        // An immediate win, and a double four only the threat search finds.
+        let positions = [
+            Board::from_moves(5, 3, &[(0, Player::X), (10, Player::O), (1, Player::X)]),
+            Board::from_moves(
+                9,
+                5,
+                &[
+                    (37, Player::X),
+                    (36, Player::O),
+                    (38, Player::X),
+                    (4, Player::O),
+                    (39, Player::X),
+                    (80, Player::O),
+                    (13, Player::X),
+                    (72, Player::O),
+                    (22, Player::X),
+                    (8, Player::O),
+                    (31, Player::X),
+                    (0, Player::O),
+                ],
+            ),
+        ];
+
+        for (board, win) in positions.iter().zip([2, 40]) {
+            let mut b = mct_bot::Bot::new(Duration::from_millis(50));
+            b.find_next_move(&Board::new(board.n(), board.k()), Player::X)
+                .expect("bot should find a move");
+            assert!(b.export_tree(1, 0).is_some());
+            assert!(!b.candidates().is_empty());
+
+            let player = board.to_move();
+            assert_eq!(b.find_next_move(board, player).ok(), Some(win));
+            assert!(b.export_tree(1, 0).is_none());
+            assert!(b.candidates().is_empty());
+            assert_eq!(b.iterations(), 0);
+        }
     }
 }
@@ -1,4 +1,16 @@
 mod bot_board;
+mod bot_config;
+mod clock;
+mod difficulty;
 mod mct_bot;
+mod rollout;
+mod search;
+mod tree;
 
-pub use mct_bot::Bot;
+pub use bot_config::BotConfig;
+pub use clock::Clock;
+pub use difficulty::Difficulty;
+pub use mct_bot::{Bot, BotError, Candidate};
+pub use rollout::RolloutPolicy;
+pub use search::{Ponder, Progress, Search, SearchResult, StopHandle};
+pub use tree::{TreeFormat, TreeNode};
@@ -0,0 +1,50 @@
+use rand::random_range;
+
+use crate::board::Player;
+use crate::mct_bot::bot_board::BotBoard;
+
+/// How many random moves a local rollout looks at for one next to a stone.
+const LOCAL_TRIES: usize = 4;
+
+/// How playouts pick their moves.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloutPolicy {
+    /// Any legal move, equally likely
+    Uniform,
+    /// Prefer moves next to existing stones
+    Local,
+    /// Win when possible, block the opponent's win, otherwise play locally
+    #[default]
+    Tactical,
+}
+
+impl RolloutPolicy {
+    /// The index into `moves` of the next playout move for `player`.
+    pub(super) fn choose(self, board: &mut BotBoard, moves: &[i16], player: Player) -> usize {
+        if self == RolloutPolicy::Tactical {
+            let (winning_move, blocking_moves) = board.terminating_moves(player);
+            if let Some(m) = winning_move.or(blocking_moves.first().copied())
+                && let Some(idx) = moves.iter().position(|&c| c == m)
+            {
+                return idx;
+            }
+        }
+
+        let mut idx = random_range(0..moves.len());
+        if self == RolloutPolicy::Uniform {
+            return idx;
+        }
+
+        for _ in 1..LOCAL_TRIES {
+            if board.has_neighbour(moves[idx]) {
+                break;
+            }
+            idx = random_range(0..moves.len());
+        }
+
+        idx
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::mct_bot::{Bot, BotError};
+
+/// How often a running search reports progress.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A shared flag that stops a search from another thread. Clones share the
+/// flag.
+#[derive(Debug, Clone, Default)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A snapshot of a running search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// The most visited root move so far
+    pub best_move: i16,
+    pub visits: i32,
+    pub iterations: usize,
+    pub elapsed: Duration,
+}
+
+pub type SearchResult = (Bot, Result<i16, BotError>);
+
+/// A search running on its own thread, see `Bot::start_search`.
+pub struct Search {
+    stop: StopHandle,
+    progress: Receiver<Progress>,
+    latest: Option<Progress>,
+    handle: Option<JoinHandle<SearchResult>>,
+}
+
+impl Search {
+    pub(super) fn new(
+        stop: StopHandle,
+        progress: Receiver<Progress>,
+        handle: JoinHandle<SearchResult>,
+    ) -> Self {
+        Self {
+            stop,
+            progress,
+            latest: None,
+            handle: Some(handle),
+        }
+    }
+
+    /// A handle for stopping the search from elsewhere.
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    /// The latest progress report, if the search has made any yet.
+    pub fn progress(&mut self) -> Option<&Progress> {
+        if let Some(progress) = self.progress.try_iter().last() {
+            self.latest = Some(progress);
+        }
+
+        self.latest.as_ref()
+    }
+
+    /// Whether the search has ended, so that `join` won't block.
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(|h| h.is_finished())
+    }
+
+    /// Waits for the search to use up its time.
+    pub fn join(mut self) -> SearchResult {
+        self.handle
+            .take()
+            .expect("search should only be joined once")
+            .join()
+            .expect("search thread should not panic")
+    }
+
+    /// Stops the search now and returns the best move found so far.
+    pub fn stop(self) -> SearchResult {
+        self.stop.stop();
+        self.join()
+    }
+}
+
+/// A dropped search stops its thread rather than running to the end.
+impl Drop for Search {
+    fn drop(&mut self) {
+        self.stop.stop();
+    }
+}
+
+/// A bot searching in the background, see `Bot::ponder`.
+pub struct Ponder {
+    stop: StopHandle,
+    handle: Option<JoinHandle<Bot>>,
+}
+
+impl Ponder {
+    pub(super) fn new(stop: StopHandle, handle: JoinHandle<Bot>) -> Self {
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stops the search and hands the bot back with its tree.
+    pub fn stop(mut self) -> Bot {
+        self.stop.stop();
+        self.handle
+            .take()
+            .expect("ponder should only be stopped once")
+            .join()
+            .expect("ponder thread should not panic")
+    }
+}
+
+/// A dropped ponder stops its thread rather than searching forever.
+impl Drop for Ponder {
+    fn drop(&mut self) {
+        self.stop.stop();
+    }
+}
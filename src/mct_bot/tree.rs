@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::board::Player;
+
+#[cfg(test)]
+mod tests;
+
+/// How an exported search tree is written.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum TreeFormat {
+    /// Graphviz DOT, to render with e.g. `dot -Tsvg`
+    Dot,
+    /// One nested JSON object
+    Json,
+}
+
+/// A copy of part of the search tree, see `Bot::export_tree`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TreeNode {
+    /// `None` at the root
+    #[serde(rename = "move")]
+    pub game_move: Option<i16>,
+    pub coord: Option<String>,
+    /// Who played `move`; at the root, who is to move
+    pub player: Player,
+    /// For `player`
+    pub wins: f32,
+    pub visits: i32,
+    /// The value selection gives the node among its siblings, `None` at the
+    /// root and for nodes not visited yet
+    pub uct: Option<f32>,
+    /// Most visited first
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    pub fn write<W: Write>(&self, format: TreeFormat, w: &mut W) -> Result<(), Box<dyn Error>> {
+        match format {
+            TreeFormat::Dot => {
+                writeln!(w, "digraph mcts {{")?;
+                writeln!(w, "    node [shape=box];")?;
+                self.write_dot(w, &mut 0)?;
+                writeln!(w, "}}")?;
+            }
+            TreeFormat::Json => {
+                serde_json::to_writer(&mut *w, self)?;
+                writeln!(w)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the node as `n<id>`, taking ids from `next_id`, then its
+    /// subtree. Returns the node's id.
+    fn write_dot<W: Write>(&self, w: &mut W, next_id: &mut usize) -> io::Result<usize> {
+        let id = *next_id;
+        *next_id += 1;
+        writeln!(w, "    n{id} [label=\"{}\"];", self.label())?;
+
+        for child in &self.children {
+            let child_id = child.write_dot(w, next_id)?;
+            writeln!(w, "    n{id} -> n{child_id};")?;
+        }
+
+        Ok(id)
+    }
+
+    fn label(&self) -> String {
+        let mut label = match &self.coord {
+            Some(coord) => format!("{coord} {}", self.player),
+            None => format!("root, {} to move", self.player),
+        };
+
+        label += &format!("\\n{:.1}/{} wins", self.wins, self.visits);
+        if let Some(uct) = self.uct {
+            label += &format!("\\nuct {uct:.3}");
+        }
+
+        label
+    }
+}
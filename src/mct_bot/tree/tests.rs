@@ -0,0 +1,59 @@
+use crate::board::Player;
+use crate::mct_bot::tree::{TreeFormat, TreeNode};
+
+mod tests {
+    use super::*;
+
+    fn tree() -> TreeNode {
+        let leaf = |m: i16, coord: &str, visits| TreeNode {
+            game_move: Some(m),
+            coord: Some(coord.to_string()),
+            player: Player::X,
+            wins: visits as f32 / 2.0,
+            visits,
+            uct: Some(0.5),
+            children: vec![],
+        };
+
+        TreeNode {
+            game_move: None,
+            coord: None,
+            player: Player::X,
+            wins: 5.0,
+            visits: 10,
+            uct: None,
+            children: vec![leaf(4, "b2", 6), leaf(0, "a3", 3)],
+        }
+    }
+
+    #[test]
+    fn dot_links_children() {
+        let mut out = vec![];
+        tree()
+            .write(TreeFormat::Dot, &mut out)
+            .expect("dot should be written");
+        let dot = String::from_utf8(out).expect("dot should be utf-8");
+
+        assert!(dot.starts_with("digraph mcts {"));
+        assert!(dot.contains(r#"n0 [label="root, X to move\n5.0/10 wins"];"#));
+        assert!(dot.contains(r#"n1 [label="b2 X\n3.0/6 wins\nuct 0.500"];"#));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n0 -> n2;"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn json_nests_children() {
+        let mut out = vec![];
+        tree()
+            .write(TreeFormat::Json, &mut out)
+            .expect("json should be written");
+        let json: serde_json::Value = serde_json::from_slice(&out).expect("output should be json");
+
+        assert_eq!(json["move"], serde_json::Value::Null);
+        assert_eq!(json["visits"], 10);
+        assert_eq!(json["children"][0]["move"], 4);
+        assert_eq!(json["children"][1]["coord"], "a3");
+        assert_eq!(json["children"][1]["children"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,332 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::alphabeta::Evaluation;
+use crate::board::{self, Board, Notation, Player};
+use crate::mct_bot::Candidate;
+use crate::solver::Solution;
+
+#[cfg(test)]
+mod tests;
+
+/// How command results are written to stdout. Diagnostics always go to
+/// stderr, so stdout only ever holds results.
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    /// The classic `key=value` lines and boards
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl Format {
+    pub fn emit<R: Record, W: Write>(self, record: &R, w: &mut W) -> Result<(), Box<dyn Error>> {
+        match self {
+            Format::Text => record.write_text(w)?,
+            Format::Json => {
+                serde_json::to_writer(&mut *w, record)?;
+                writeln!(w)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A result that can be written either as text or as a JSON line.
+pub trait Record: Serialize {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+fn elapsed_ms(elapsed: Duration) -> u64 {
+    elapsed.as_millis() as u64
+}
+
+#[derive(Serialize)]
+pub struct MoveRecord {
+    #[serde(rename = "move")]
+    pub game_move: String,
+    pub index: i16,
+    pub player: Player,
+    pub winner: Option<Player>,
+    pub draw: bool,
+    pub iterations: usize,
+    pub elapsed_ms: u64,
+    pub n: i16,
+    /// The board after the move, in the `<cells>` state format.
+    pub board: String,
+}
+
+impl MoveRecord {
+    /// Describes `m`, which `player` has just played on `board`.
+    pub fn new(
+        board: &Board,
+        m: i16,
+        player: Player,
+        iterations: usize,
+        elapsed: Duration,
+        notation: Notation,
+    ) -> Self {
+        let winner = board
+            .winning_line()
+            .first()
+            .and_then(|&m| board.cells()[m as usize]);
+
+        Self {
+            game_move: notation.format(m, board.n()),
+            index: m,
+            player,
+            winner,
+            draw: winner.is_none() && board.is_tie(),
+            iterations,
+            elapsed_ms: elapsed_ms(elapsed),
+            n: board.n(),
+            board: board::to_cell_string(board.cells()),
+        }
+    }
+}
+
+impl Record for MoveRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "next_move={}", self.game_move)?;
+        if let Some(winner) = self.winner {
+            write!(w, " winner={winner}")?;
+        }
+        writeln!(w)
+    }
+}
+
+#[derive(Serialize)]
+pub struct GameRecord {
+    pub winner: Option<Player>,
+    pub draw: bool,
+    pub moves: Vec<String>,
+    pub elapsed_ms: u64,
+    pub n: i16,
+    pub board: String,
+}
+
+impl GameRecord {
+    pub fn new(
+        board: &Board,
+        moves: Vec<String>,
+        winner: Option<Player>,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            winner,
+            draw: winner.is_none(),
+            moves,
+            elapsed_ms: elapsed_ms(elapsed),
+            n: board.n(),
+            board: board::to_cell_string(board.cells()),
+        }
+    }
+}
+
+impl Record for GameRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self.winner {
+            Some(winner) => writeln!(w, "chicken dinner {winner}"),
+            None => writeln!(w, "no dinner"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CandidateRecord {
+    #[serde(rename = "move")]
+    pub game_move: String,
+    pub visits: i32,
+    pub win_rate: f32,
+}
+
+#[derive(Serialize)]
+pub struct AnalysisRecord {
+    pub best_move: String,
+    pub player: Player,
+    pub iterations: usize,
+    pub elapsed_ms: u64,
+    pub candidates: Vec<CandidateRecord>,
+}
+
+impl AnalysisRecord {
+    pub fn new(
+        n: i16,
+        best: i16,
+        player: Player,
+        iterations: usize,
+        elapsed: Duration,
+        candidates: &[Candidate],
+        notation: Notation,
+    ) -> Self {
+        Self {
+            best_move: notation.format(best, n),
+            player,
+            iterations,
+            elapsed_ms: elapsed_ms(elapsed),
+            candidates: candidates
+                .iter()
+                .map(|c| CandidateRecord {
+                    game_move: notation.format(c.game_move, n),
+                    visits: c.visits,
+                    win_rate: c.win_rate(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Record for AnalysisRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "best_move={} iterations={}",
+            self.best_move, self.iterations
+        )?;
+        for c in &self.candidates {
+            writeln!(
+                w,
+                "{:>5} {:>8} visits {:>5.1}%",
+                c.game_move,
+                c.visits,
+                100.0 * c.win_rate
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct SolveRecord {
+    pub outcome: String,
+    pub best_move: Option<String>,
+    pub player: Player,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+}
+
+impl SolveRecord {
+    pub fn new(
+        n: i16,
+        solution: &Solution,
+        player: Player,
+        elapsed: Duration,
+        notation: Notation,
+    ) -> Self {
+        Self {
+            outcome: solution.outcome.to_string(),
+            best_move: solution.best_move.map(|m| notation.format(m, n)),
+            player,
+            nodes: solution.nodes,
+            elapsed_ms: elapsed_ms(elapsed),
+        }
+    }
+}
+
+impl Record for SolveRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "outcome={}", self.outcome)?;
+        if let Some(m) = &self.best_move {
+            write!(w, " best_move={m}")?;
+        }
+        writeln!(w, " nodes={}", self.nodes)
+    }
+}
+
+#[derive(Serialize)]
+pub struct EvaluationRecord {
+    pub score: i32,
+    pub best_move: Option<String>,
+    pub player: Player,
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+}
+
+impl EvaluationRecord {
+    pub fn new(
+        n: i16,
+        evaluation: &Evaluation,
+        player: Player,
+        depth: u32,
+        elapsed: Duration,
+        notation: Notation,
+    ) -> Self {
+        Self {
+            score: evaluation.score,
+            best_move: evaluation.best_move.map(|m| notation.format(m, n)),
+            player,
+            depth,
+            nodes: evaluation.nodes,
+            elapsed_ms: elapsed_ms(elapsed),
+        }
+    }
+}
+
+impl Record for EvaluationRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "score={}", self.score)?;
+        if let Some(m) = &self.best_move {
+            write!(w, " best_move={m}")?;
+        }
+        writeln!(w, " nodes={}", self.nodes)
+    }
+}
+
+#[derive(Serialize)]
+pub struct BenchRecord {
+    pub run: u32,
+    pub iterations: usize,
+    pub elapsed_ms: u64,
+    pub iterations_per_second: f64,
+}
+
+impl BenchRecord {
+    pub fn new(run: u32, iterations: usize, elapsed: Duration) -> Self {
+        Self {
+            run,
+            iterations,
+            elapsed_ms: elapsed_ms(elapsed),
+            iterations_per_second: iterations as f64 / elapsed.as_secs_f64(),
+        }
+    }
+}
+
+impl Record for BenchRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "run {}: {} iterations, {:.0}/s",
+            self.run, self.iterations, self.iterations_per_second
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct BenchSummary {
+    pub runs: u32,
+    pub mean_iterations_per_second: f64,
+}
+
+impl Record for BenchSummary {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "mean: {:.0}/s", self.mean_iterations_per_second)
+    }
+}
+
+/// A state file's contents, for `convert` without an output file.
+#[derive(Serialize)]
+pub struct StateRecord {
+    pub contents: String,
+}
+
+impl Record for StateRecord {
+    fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{}", self.contents)
+    }
+}
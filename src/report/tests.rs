@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use crate::board::{Board, Notation, Player};
+use crate::report::{Format, GameRecord, MoveRecord};
+
+mod tests {
+    use super::*;
+
+    fn emit_to_string<R: crate::report::Record>(format: Format, record: &R) -> String {
+        let mut out = vec![];
+        format.emit(record, &mut out).expect("record should emit");
+        String::from_utf8(out).expect("output should be utf-8")
+    }
+
+    #[test]
+    fn move_record_text() {
+        let mut board = Board::new(3, 3);
+        board
+            .apply_move(4, Player::X)
+            .expect("move should be valid");
+        let record = MoveRecord::new(&board, 4, Player::X, 10, Duration::ZERO, Notation::Coord);
+
+        assert_eq!(emit_to_string(Format::Text, &record), "next_move=b2\n");
+    }
+
+    #[test]
+    fn move_record_json() {
+        let mut board = Board::new(3, 3);
+        for m in [0, 3, 1, 4] {
+            board
+                .apply_move(m, board.to_move())
+                .expect("move should be valid");
+        }
+        board
+            .apply_move(2, Player::X)
+            .expect("move should be valid");
+        let record = MoveRecord::new(
+            &board,
+            2,
+            Player::X,
+            0,
+            Duration::from_millis(12),
+            Notation::Index,
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&emit_to_string(Format::Json, &record)).expect("json");
+        assert_eq!(json["move"], "2");
+        assert_eq!(json["winner"], "X");
+        assert_eq!(json["draw"], false);
+        assert_eq!(json["elapsed_ms"], 12);
+        assert_eq!(json["board"], "XXXOO____");
+    }
+
+    #[test]
+    fn game_record_text() {
+        let board = Board::new(3, 3);
+        let record = GameRecord::new(&board, vec![], Some(Player::O), Duration::ZERO);
+        assert_eq!(emit_to_string(Format::Text, &record), "chicken dinner O\n");
+
+        let record = GameRecord::new(&board, vec![], None, Duration::ZERO);
+        assert_eq!(emit_to_string(Format::Text, &record), "no dinner\n");
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::board::{self, Board, Notation, Player, Variant};
+use crate::mct_bot::{Bot, BotConfig, Difficulty, Progress, StopHandle, TreeNode};
+
+#[cfg(test)]
+mod tests;
+
+/// How long a search takes when neither the request nor the server's bot
+/// settings say.
+pub const DEFAULT_THINK_TIME: Duration = Duration::from_millis(1000);
+const MAX_THINK_MS: u64 = 30_000;
+const CANDIDATE_COUNT: usize = 5;
+const MAX_TREE_DEPTH: usize = 6;
+
+/// A position as posted by clients: the cells in the `<cells>` state format
+/// (`X`, `O` and `_`, row by row from the top left).
+#[derive(Deserialize)]
+struct PositionRequest {
+    cells: String,
+    k: Option<i8>,
+    variant: Option<Variant>,
+    player: Option<Player>,
+    difficulty: Option<Difficulty>,
+    think_ms: Option<u64>,
+    /// Names the search so `/progress` and `/stop` can refer to it
+    id: Option<String>,
+    /// Include the search tree down to this many moves in the response
+    tree_depth: Option<usize>,
+    /// Leave out tree nodes with fewer visits than this
+    tree_min_visits: Option<i32>,
+}
+
+/// Why a posted position was turned down.
+enum PositionError {
+    /// The request asks for rules no game can be played by
+    Rules(String),
+    /// The position can't be read or couldn't come from a real game
+    Invalid(String),
+}
+
+#[derive(Deserialize)]
+struct SearchRequest {
+    id: String,
+}
+
+/// A `/move` search that was given an id, while it runs.
+struct RunningSearch {
+    stop: StopHandle,
+    progress: Option<Progress>,
+    n: i16,
+}
+
+type Searches = Arc<Mutex<HashMap<String, RunningSearch>>>;
+
+#[derive(Serialize)]
+struct CandidateResponse {
+    #[serde(rename = "move")]
+    game_move: i16,
+    coord: String,
+    visits: i32,
+    win_rate: f32,
+}
+
+#[derive(Serialize)]
+struct MoveResponse {
+    #[serde(rename = "move")]
+    game_move: i16,
+    coord: String,
+    player: Player,
+    winner: Option<Player>,
+    draw: bool,
+    iterations: usize,
+    candidates: Vec<CandidateResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tree: Option<TreeNode>,
+}
+
+#[derive(Serialize)]
+struct ProgressResponse {
+    id: String,
+    #[serde(rename = "move")]
+    game_move: Option<i16>,
+    coord: Option<String>,
+    visits: i32,
+    iterations: usize,
+    elapsed_ms: u64,
+}
+
+#[derive(Serialize)]
+struct StopResponse {
+    id: String,
+    stopped: bool,
+}
+
+#[derive(Serialize)]
+struct ValidateResponse {
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to_move: Option<Player>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    winner: Option<Player>,
+    draw: bool,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// What requests get for the settings they leave out.
+#[derive(Clone, Copy)]
+struct Defaults {
+    k: i8,
+    variant: Variant,
+    config: BotConfig,
+}
+
+/// HTTP/JSON front to the bot. Every request is handled on its own thread
+/// with its own `Bot`, so slow searches don't hold up other clients. Searches
+/// started with an `id` can be watched through `/progress` and cut short
+/// through `/stop`.
+pub struct Server {
+    http: tiny_http::Server,
+    defaults: Defaults,
+    searches: Searches,
+}
+
+impl Server {
+    pub fn bind(addr: &str, k: i8) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self {
+            http: tiny_http::Server::http(addr)?,
+            defaults: Defaults {
+                k,
+                variant: Variant::default(),
+                config: BotConfig::new().thinking_time(DEFAULT_THINK_TIME),
+            },
+            searches: Searches::default(),
+        })
+    }
+
+    /// The rule variant for positions posted without one.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.defaults.variant = variant;
+        self
+    }
+
+    /// The bot settings for searches that don't ask for a difficulty.
+    pub fn with_config(mut self, config: BotConfig) -> Self {
+        self.defaults.config = config;
+        self
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            let defaults = self.defaults;
+            let searches = Arc::clone(&self.searches);
+            thread::spawn(move || handle(request, defaults, &searches));
+        }
+    }
+}
+
+fn handle(mut request: Request, defaults: Defaults, searches: &Searches) {
+    let mut body = String::new();
+    let (status, json) = if request.as_reader().read_to_string(&mut body).is_err() {
+        error(400, "unreadable body")
+    } else {
+        match (request.method(), request.url()) {
+            (Method::Post, "/move") => next_move(&body, defaults, searches),
+            (Method::Post, "/validate") => validate(&body, defaults),
+            (Method::Post, "/progress") => progress(&body, searches),
+            (Method::Post, "/stop") => stop(&body, searches),
+            (_, "/move") | (_, "/validate") | (_, "/progress") | (_, "/stop") => {
+                error(405, "use POST")
+            }
+            _ => error(404, "no such endpoint"),
+        }
+    };
+
+    log::debug!("{} {} -> {status}", request.method(), request.url());
+
+    let header = Header::from_bytes("Content-Type", "application/json").expect("static header");
+    let response = Response::from_string(json)
+        .with_status_code(status)
+        .with_header(header);
+    let _ = request.respond(response);
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    let body = ErrorResponse {
+        error: message.to_string(),
+    };
+    (
+        status,
+        serde_json::to_string(&body).expect("error should serialize"),
+    )
+}
+
+fn parse_position(
+    body: &str,
+    defaults: Defaults,
+) -> Result<(Board, PositionRequest), PositionError> {
+    let request: PositionRequest =
+        serde_json::from_str(body).map_err(|e| PositionError::Invalid(e.to_string()))?;
+
+    let cells = request.cells.trim();
+    if let Some(c) = cells.chars().find(|c| !['X', 'O', '_'].contains(c)) {
+        return Err(PositionError::Invalid(format!("invalid cell '{c}'")));
+    }
+    let (n, cells) = board::from_cell_string_to_state(cells)
+        .map_err(|_| PositionError::Invalid("cells do not form a square board".to_string()))?;
+    let k = request.k.unwrap_or(defaults.k);
+    if board::check_rules(n as i16, k).is_err() {
+        return Err(PositionError::Rules(format!("k must be between 1 and {n}")));
+    }
+    let board = Board::new_from_state(n as i16, k, cells)
+        .with_variant(request.variant.unwrap_or(defaults.variant));
+    Ok((board, request))
+}
+
+fn winner(board: &Board) -> Option<Player> {
+    board
+        .winning_line()
+        .first()
+        .and_then(|&m| board.cells()[m as usize])
+}
+
+fn next_move(body: &str, defaults: Defaults, searches: &Searches) -> (u16, String) {
+    let (mut board, request) = match parse_position(body, defaults) {
+        Ok(position) => position,
+        Err(PositionError::Rules(e) | PositionError::Invalid(e)) => return error(400, &e),
+    };
+
+    if winner(&board).is_some() || board.is_tie() {
+        return error(422, "the game is already over");
+    }
+
+    let player = request.player.unwrap_or(board.to_move());
+    let config = match request.difficulty {
+        Some(difficulty) => difficulty.config(),
+        None => defaults.config,
+    };
+    let config = match request.think_ms {
+        Some(think_ms) => config.thinking_time(Duration::from_millis(think_ms.min(MAX_THINK_MS))),
+        None => config,
+    };
+    let mut bot = Bot::with_config(config);
+
+    let stop = StopHandle::new();
+    if let Some(id) = &request.id {
+        let mut searches = searches.lock().expect("searches lock");
+        if searches.contains_key(id) {
+            return error(409, "a search with this id is already running");
+        }
+        searches.insert(
+            id.clone(),
+            RunningSearch {
+                stop: stop.clone(),
+                progress: None,
+                n: board.n(),
+            },
+        );
+    }
+
+    let result = bot.search_with(&board, player, &stop, |p| {
+        if let Some(id) = &request.id
+            && let Some(search) = searches.lock().expect("searches lock").get_mut(id)
+        {
+            search.progress = Some(p.clone());
+        }
+    });
+    if let Some(id) = &request.id {
+        searches.lock().expect("searches lock").remove(id);
+    }
+
+    let m = match result {
+        Ok(m) => m,
+        Err(e) => return error(422, &e.to_string()),
+    };
+    let winner = board
+        .apply_move(m, player)
+        .expect("bot move should be valid");
+
+    let n = board.n();
+    let response = MoveResponse {
+        game_move: m,
+        coord: Notation::Coord.format(m, n),
+        player,
+        winner,
+        draw: winner.is_none() && board.is_tie(),
+        iterations: bot.iterations(),
+        candidates: bot
+            .candidates()
+            .iter()
+            .take(CANDIDATE_COUNT)
+            .map(|c| CandidateResponse {
+                game_move: c.game_move,
+                coord: Notation::Coord.format(c.game_move, n),
+                visits: c.visits,
+                win_rate: c.win_rate(),
+            })
+            .collect(),
+        tree: request.tree_depth.and_then(|depth| {
+            bot.export_tree(
+                depth.min(MAX_TREE_DEPTH),
+                request.tree_min_visits.unwrap_or(1),
+            )
+        }),
+    };
+
+    (
+        200,
+        serde_json::to_string(&response).expect("response should serialize"),
+    )
+}
+
+/// A position is valid if it parses and could come from a real game: stone
+/// counts at most one apart and at most one player with a completed line.
+fn check_position(body: &str, defaults: Defaults) -> Result<Board, PositionError> {
+    let (board, _) = parse_position(body, defaults)?;
+
+    let count = |p: Player| board.cells().iter().filter(|&&c| c == Some(p)).count();
+    if count(Player::X).abs_diff(count(Player::O)) > 1 {
+        return Err(PositionError::Invalid(
+            "stone counts differ by more than one".to_string(),
+        ));
+    }
+
+    let line = board.winning_line();
+    if line
+        .iter()
+        .any(|&m| board.cells()[m as usize] != winner(&board))
+    {
+        return Err(PositionError::Invalid(
+            "both players have a completed line".to_string(),
+        ));
+    }
+
+    Ok(board)
+}
+
+fn validate(body: &str, defaults: Defaults) -> (u16, String) {
+    let response = match check_position(body, defaults) {
+        Ok(board) => ValidateResponse {
+            valid: true,
+            error: None,
+            n: Some(board.n()),
+            to_move: Some(board.to_move()),
+            winner: winner(&board),
+            draw: winner(&board).is_none() && board.is_tie(),
+        },
+        Err(PositionError::Rules(e)) => return error(400, &e),
+        Err(PositionError::Invalid(e)) => ValidateResponse {
+            valid: false,
+            error: Some(e),
+            n: None,
+            to_move: None,
+            winner: None,
+            draw: false,
+        },
+    };
+
+    (
+        200,
+        serde_json::to_string(&response).expect("response should serialize"),
+    )
+}
+
+fn progress(body: &str, searches: &Searches) -> (u16, String) {
+    let request: SearchRequest = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return error(400, &e.to_string()),
+    };
+
+    let searches = searches.lock().expect("searches lock");
+    let Some(search) = searches.get(&request.id) else {
+        return error(404, "no such search");
+    };
+
+    let progress = search.progress.as_ref();
+    let response = ProgressResponse {
+        id: request.id.clone(),
+        game_move: progress.map(|p| p.best_move),
+        coord: progress.map(|p| Notation::Coord.format(p.best_move, search.n)),
+        visits: progress.map_or(0, |p| p.visits),
+        iterations: progress.map_or(0, |p| p.iterations),
+        elapsed_ms: progress.map_or(0, |p| p.elapsed.as_millis() as u64),
+    };
+
+    (
+        200,
+        serde_json::to_string(&response).expect("response should serialize"),
+    )
+}
+
+/// Stops a running search; its `/move` request then answers with the best
+/// move found so far.
+fn stop(body: &str, searches: &Searches) -> (u16, String) {
+    let request: SearchRequest = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return error(400, &e.to_string()),
+    };
+
+    let searches = searches.lock().expect("searches lock");
+    let Some(search) = searches.get(&request.id) else {
+        return error(404, "no such search");
+    };
+    search.stop.stop();
+
+    let response = StopResponse {
+        id: request.id,
+        stopped: true,
+    };
+    (
+        200,
+        serde_json::to_string(&response).expect("response should serialize"),
+    )
+}
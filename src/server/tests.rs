@@ -0,0 +1,212 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+
+use crate::board::Variant;
+use crate::server::Server;
+
+mod tests {
+    use super::*;
+
+    fn start() -> SocketAddr {
+        serve(Server::bind("127.0.0.1:0", 3).expect("server should bind"))
+    }
+
+    fn serve(server: Server) -> SocketAddr {
+        let addr = server.local_addr().expect("server should have an address");
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    fn post(addr: SocketAddr, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(addr).expect("server should accept");
+        write!(
+            stream,
+            "POST {path} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .expect("request should be sent");
+
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("response should be read");
+
+        let status = response[9..12].parse().expect("status should be a number");
+        let (_, json) = response
+            .split_once("\r\n\r\n")
+            .expect("response should have a body");
+        (
+            status,
+            serde_json::from_str(json).expect("body should be json"),
+        )
+    }
+
+    #[test]
+    fn move_finds_win() {
+        let addr = start();
+        let (status, json) = post(
+            addr,
+            "/move",
+            r#"{"cells": "XX_OO____", "player": "X", "think_ms": 100}"#,
+        );
+
+        assert_eq!(status, 200);
+        assert_eq!(json["move"], 2);
+        assert_eq!(json["coord"], "c3");
+        assert_eq!(json["winner"], "X");
+    }
+
+    #[test]
+    fn concurrent_moves() {
+        let addr = start();
+        let requests: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(move || {
+                    post(addr, "/move", r#"{"cells": "_________", "think_ms": 200}"#)
+                })
+            })
+            .collect();
+
+        for request in requests {
+            let (status, json) = request.join().expect("request thread should finish");
+            assert_eq!(status, 200);
+            assert_eq!(json["player"], "X");
+            assert!(
+                json["iterations"]
+                    .as_u64()
+                    .expect("iterations should be a number")
+                    > 0
+            );
+        }
+    }
+
+    #[test]
+    fn move_with_difficulty() {
+        let addr = start();
+        let (status, json) = post(
+            addr,
+            "/move",
+            r#"{"cells": "____X____", "difficulty": "easy"}"#,
+        );
+
+        assert_eq!(status, 200);
+        assert_eq!(json["player"], "O");
+        assert_ne!(json["move"], 4);
+    }
+
+    #[test]
+    fn validate() {
+        let addr = start();
+
+        let (status, json) = post(addr, "/validate", r#"{"cells": "XXXOO____"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["winner"], "X");
+
+        let (_, json) = post(addr, "/validate", r#"{"cells": "XXX______"}"#);
+        assert_eq!(json["valid"], false);
+
+        let (_, json) = post(addr, "/validate", r#"{"cells": "XX?______"}"#);
+        assert_eq!(json["valid"], false);
+    }
+
+    #[test]
+    fn server_variant_applies() {
+        // X's four in a row is an overline under the exact-k rules.
+        let cells = r#"{"cells": "XXXXO_O__O______"}"#;
+        let (_, json) = post(start(), "/validate", cells);
+        assert_eq!(json["winner"], "X");
+
+        let server = Server::bind("127.0.0.1:0", 3)
+            .expect("server should bind")
+            .with_variant(Variant::Exact);
+        let (_, json) = post(serve(server), "/validate", cells);
+        assert_eq!(json["valid"], true);
+        assert!(json["winner"].is_null());
+    }
+
+    #[test]
+    fn bad_requests() {
+        let addr = start();
+
+        assert_eq!(post(addr, "/move", "not json").0, 400);
+        assert_eq!(post(addr, "/move", r#"{"cells": "XXXOO____"}"#).0, 422);
+        assert_eq!(post(addr, "/nowhere", "{}").0, 404);
+    }
+
+    #[test]
+    fn row_length_out_of_range() {
+        let addr = start();
+
+        for k in [0, -1, 4] {
+            let body = format!(r#"{{"cells": "X___O____", "k": {k}, "think_ms": 10}}"#);
+            let (status, json) = post(addr, "/move", &body);
+            assert_eq!(status, 400);
+            assert_eq!(json["error"], "k must be between 1 and 3");
+            assert_eq!(post(addr, "/validate", &body).0, 400);
+        }
+        let (status, _) = post(addr, "/validate", r#"{"cells": "X___O____", "k": 3}"#);
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn progress_and_stop() {
+        let addr = start();
+        let search = thread::spawn(move || {
+            post(
+                addr,
+                "/move",
+                r#"{"cells": "_________", "think_ms": 20000, "id": "game-1"}"#,
+            )
+        });
+
+        let mut progress = (404, serde_json::Value::Null);
+        for _ in 0..50 {
+            thread::sleep(std::time::Duration::from_millis(100));
+            progress = post(addr, "/progress", r#"{"id": "game-1"}"#);
+            if progress.0 == 200 && progress.1["iterations"].as_u64() > Some(0) {
+                break;
+            }
+        }
+        assert_eq!(progress.0, 200);
+        assert!(progress.1["move"].is_number());
+
+        let (status, _) = post(addr, "/stop", r#"{"id": "game-1"}"#);
+        assert_eq!(status, 200);
+
+        let (status, json) = search.join().expect("search thread should finish");
+        assert_eq!(status, 200);
+        assert!(json["move"].is_number());
+
+        let (status, _) = post(addr, "/stop", r#"{"id": "game-1"}"#);
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn move_includes_tree() {
+        let addr = start();
+        let (status, json) = post(
+            addr,
+            "/move",
+            r#"{"cells": "_________", "think_ms": 100, "tree_depth": 1}"#,
+        );
+        assert_eq!(status, 200);
+
+        let tree = &json["tree"];
+        assert!(tree["move"].is_null());
+        let children = tree["children"]
+            .as_array()
+            .expect("tree should have children");
+        assert!(!children.is_empty());
+        assert!(children[0]["coord"].is_string());
+        assert!(
+            children[0]["children"]
+                .as_array()
+                .is_some_and(|c| c.is_empty())
+        );
+
+        let (_, json) = post(addr, "/move", r#"{"cells": "_________", "think_ms": 100}"#);
+        assert!(json.get("tree").is_none());
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::board::{Board, GameBoard, Player, symmetry};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(strum_macros::Display, Debug)]
+pub enum SolverError {
+    NodeLimit,
+}
+
+impl Error for SolverError {}
+
+/// The game-theoretic result for the player to move.
+#[derive(Debug, Clone, Copy, PartialEq, strum_macros::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    pub outcome: Outcome,
+    pub best_move: Option<i16>,
+    pub nodes: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct Entry {
+    value: i8,
+    bound: Bound,
+    best_move: Option<i16>,
+}
+
+/// Exhaustive win/draw/loss search: negamax with alpha-beta pruning, a
+/// transposition table keyed on the Zobrist hash, forced replies to threats
+/// and symmetric duplicates pruned. Only practical for small boards.
+pub struct Solver {
+    table: HashMap<u64, Entry>,
+    nodes: u64,
+    node_limit: Option<u64>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self {
+            table: HashMap::new(),
+            nodes: 0,
+            node_limit: None,
+        }
+    }
+
+    pub fn with_node_limit(mut self, node_limit: u64) -> Self {
+        self.node_limit = Some(node_limit);
+        self
+    }
+
+    pub fn solve(&mut self, board: &Board, player: Player) -> Result<Solution, SolverError> {
+        let mut board = board.clone();
+        board.set_to_move(player);
+        self.nodes = 0;
+
+        let (value, best_move) = self.negamax(&mut board, player, -1, 1)?;
+        let outcome = match value {
+            1 => Outcome::Win,
+            0 => Outcome::Draw,
+            _ => Outcome::Loss,
+        };
+
+        Ok(Solution {
+            outcome,
+            best_move,
+            nodes: self.nodes,
+        })
+    }
+
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        player: Player,
+        mut alpha: i8,
+        mut beta: i8,
+    ) -> Result<(i8, Option<i16>), SolverError> {
+        self.nodes += 1;
+        if self.node_limit.is_some_and(|limit| self.nodes > limit) {
+            return Err(SolverError::NodeLimit);
+        }
+
+        if let Some(&m) = board.winning_moves(player).first() {
+            return Ok((1, Some(m)));
+        }
+
+        if board.is_tie() {
+            return Ok((0, None));
+        }
+
+        let threats = board.winning_moves(player.next());
+        if threats.len() > 1 {
+            return Ok((-1, Some(threats[0])));
+        }
+
+        let key = board.zobrist();
+        let original_alpha = alpha;
+        let mut tt_move = None;
+        if let Some(entry) = self.table.get(&key) {
+            match entry.bound {
+                Bound::Exact => return Ok((entry.value, entry.best_move)),
+                Bound::Lower => alpha = alpha.max(entry.value),
+                Bound::Upper => beta = beta.min(entry.value),
+            }
+            if alpha >= beta {
+                return Ok((entry.value, entry.best_move));
+            }
+            tt_move = entry.best_move;
+        }
+
+        let mut moves = if threats.is_empty() {
+            let legal = GameBoard::legal_moves(&*board);
+            let mut moves = symmetry::unique_moves(board, &legal);
+            let centre = board.n() - 1;
+            moves.sort_by_key(|&m| {
+                let (x, y) = (m % board.n(), m / board.n());
+                (2 * x - centre).abs() + (2 * y - centre).abs()
+            });
+            moves
+        } else {
+            threats
+        };
+        if let Some(m) = tt_move
+            && let Some(i) = moves.iter().position(|&um| um == m)
+        {
+            moves[..=i].rotate_right(1);
+        }
+
+        let mut best = (-2, None);
+        for m in moves {
+            board
+                .apply_move(m, player)
+                .expect("legal move should be valid");
+            let result = self.negamax(board, player.next(), -beta, -alpha);
+            board.undo_move(m).expect("undo should be valid");
+
+            let value = -result?.0;
+            if value > best.0 {
+                best = (value, Some(m));
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best.0 <= original_alpha {
+            Bound::Upper
+        } else if best.0 >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.insert(
+            key,
+            Entry {
+                value: best.0,
+                bound,
+                best_move: best.1,
+            },
+        );
+
+        Ok(best)
+    }
+}
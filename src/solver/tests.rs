@@ -0,0 +1,50 @@
+use crate::board;
+use crate::board::{Board, Player};
+use crate::solver::{Outcome, Solver};
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_three_by_three_is_a_draw() {
+        let solution = Solver::new()
+            .solve(&Board::new(3, 3), Player::X)
+            .expect("3x3 should be solvable");
+
+        assert_eq!(solution.outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn defended_corners_are_a_draw() {
+        let board = "
+        [X][ ][ ]
+        [ ][O][ ]
+        [ ][ ][X]
+        ";
+        let (n, cells) = board::from_board_string_to_state(board).expect("board should parse");
+        let test_board = Board::new_from_state(n as i16, 3, cells);
+
+        let solution = Solver::new()
+            .solve(&test_board, Player::O)
+            .expect("position should be solvable");
+        assert_eq!(solution.outcome, Outcome::Draw);
+    }
+
+    #[test]
+    fn four_by_four_is_a_forced_win() {
+        let solution = Solver::new()
+            .solve(&Board::new(4, 3), Player::X)
+            .expect("4x4 should be solvable");
+        assert_eq!(solution.outcome, Outcome::Win);
+        assert!(solution.best_move.is_some());
+    }
+
+    #[test]
+    fn node_limit() {
+        let result = Solver::new()
+            .with_node_limit(10)
+            .solve(&Board::new(5, 4), Player::X);
+
+        assert!(result.is_err());
+    }
+}
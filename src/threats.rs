@@ -0,0 +1,272 @@
+use std::time::Instant;
+
+use crate::board::{Board, Player};
+use crate::eval;
+
+#[cfg(test)]
+mod tests;
+
+/// How many attacking moves deep a sequence of fours may go.
+const VCF_DEPTH: u32 = 12;
+/// How many attacking moves deep a sequence with threes may go; every three
+/// has several replies to try, so this stays shallow.
+const VCT_DEPTH: u32 = 4;
+/// Positions visited before the search gives up, per question asked.
+pub const NODE_LIMIT: u64 = 20_000;
+/// How many positions pass between looks at the clock.
+const DEADLINE_CHECK_INTERVAL: u64 = 64;
+
+/// Threat-space search: looks for a forced win made of threats the
+/// opponent has to answer. Victory by continuous fours (VCF) only uses
+/// moves that threaten to win at once, so every reply is forced; victory by
+/// continuous threats (VCT) also uses threes, trying every reply that could
+/// stop them. A "four" is k-1 stones with room to finish the line, a
+/// "three" is k-2.
+pub struct ThreatSearch {
+    node_limit: u64,
+    deadline: Option<Instant>,
+    nodes: u64,
+    out_of_time: bool,
+    vct: bool,
+}
+
+impl ThreatSearch {
+    pub fn new() -> Self {
+        Self {
+            node_limit: NODE_LIMIT,
+            deadline: None,
+            nodes: 0,
+            out_of_time: false,
+            vct: true,
+        }
+    }
+
+    /// Only look for sequences of fours.
+    #[allow(dead_code)]
+    pub fn vcf_only(mut self) -> Self {
+        self.vct = false;
+        self
+    }
+
+    pub fn with_node_limit(mut self, node_limit: u64) -> Self {
+        self.node_limit = node_limit;
+        self
+    }
+
+    /// Give up on any search still running at `deadline`.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Positions visited by the last search.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// The first move of a forced win for `player`, who is to move on
+    /// `board`, if there is one within reach.
+    pub fn find_win(&mut self, board: &Board, player: Player) -> Option<i16> {
+        let mut board = board.clone();
+        self.nodes = 0;
+        self.out_of_time = false;
+
+        if let Some(m) = self.vcf(&mut board, player, VCF_DEPTH) {
+            return Some(m);
+        }
+
+        // With k < 4 every empty cell makes a "three", which is no threat.
+        if self.vct && board.k() >= 4 {
+            return self.vct(&mut board, player, VCT_DEPTH);
+        }
+
+        None
+    }
+
+    /// The first move of a forced win the opponent of `player` would have
+    /// if it were their turn: a cell `player` should think about taking.
+    pub fn find_threat(&mut self, board: &Board, player: Player) -> Option<i16> {
+        self.find_win(board, player.next())
+    }
+
+    /// Counts a position, and whether the search should give up.
+    fn out_of_nodes(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && self.deadline.is_some_and(|d| Instant::now() >= d)
+        {
+            self.out_of_time = true;
+        }
+
+        self.exhausted()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.out_of_time || self.nodes > self.node_limit
+    }
+
+    fn vcf(&mut self, board: &mut Board, attacker: Player, depth: u32) -> Option<i16> {
+        if self.out_of_nodes() {
+            return None;
+        }
+
+        if let Some(&m) = board.winning_moves(attacker).first() {
+            return Some(m);
+        }
+        if depth == 0 {
+            return None;
+        }
+
+        for m in self.fours(board, attacker)? {
+            board
+                .apply_move(m, attacker)
+                .expect("threat should be legal");
+            let won = self.after_four(board, attacker, |search, board| {
+                search.vcf(board, attacker, depth - 1).is_some()
+            });
+            board.undo_move(m).expect("undo should be valid");
+
+            if won {
+                return Some(m);
+            }
+            if self.exhausted() {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    fn vct(&mut self, board: &mut Board, attacker: Player, depth: u32) -> Option<i16> {
+        if let Some(m) = self.vcf(board, attacker, depth) {
+            return Some(m);
+        }
+        if depth == 0 || self.exhausted() {
+            return None;
+        }
+
+        let defender = attacker.next();
+        for m in self.fours(board, attacker)? {
+            board
+                .apply_move(m, attacker)
+                .expect("threat should be legal");
+            let won = self.after_four(board, attacker, |search, board| {
+                search.vct(board, attacker, depth - 1).is_some()
+            });
+            board.undo_move(m).expect("undo should be valid");
+
+            if won {
+                return Some(m);
+            }
+            if self.exhausted() {
+                return None;
+            }
+        }
+
+        // A three only threatens anything while the defender has no four.
+        if !board.winning_moves(defender).is_empty() {
+            return None;
+        }
+
+        // Moves that make the most threats at once first.
+        let mut threes = board.threat_moves(attacker, board.k() - 3);
+        threes.sort_by_cached_key(|&m| std::cmp::Reverse(eval::score_move(board, m, attacker)));
+        for m in threes {
+            board
+                .apply_move(m, attacker)
+                .expect("threat should be legal");
+            let won = self.is_three(board, attacker)
+                && self.every_reply_loses(board, attacker, depth - 1);
+            board.undo_move(m).expect("undo should be valid");
+
+            if won {
+                return Some(m);
+            }
+            if self.exhausted() {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    /// The moves that make a four for `attacker`, or `None` if the defender
+    /// threatens more than one win, which a single move can't stop.
+    fn fours(&self, board: &Board, attacker: Player) -> Option<Vec<i16>> {
+        let defender_wins = board.winning_moves(attacker.next());
+        if defender_wins.len() > 1 {
+            return None;
+        }
+
+        let mut moves = board.threat_moves(attacker, board.k() - 2);
+        // Against a four of the defender's, only a four that also blocks it
+        // keeps the initiative.
+        if let Some(block) = defender_wins.first() {
+            moves.retain(|m| m == block);
+        }
+
+        Some(moves)
+    }
+
+    /// After `attacker` made a four: a win if it is a double four, otherwise
+    /// `continue_from` decides once the defender blocks. Either way the
+    /// defender must not win first.
+    fn after_four<F: FnOnce(&mut Self, &mut Board) -> bool>(
+        &mut self,
+        board: &mut Board,
+        attacker: Player,
+        continue_from: F,
+    ) -> bool {
+        let defender = attacker.next();
+        let wins = board.winning_moves(attacker);
+        if wins.is_empty() || !board.winning_moves(defender).is_empty() {
+            return false;
+        }
+        if wins.len() > 1 {
+            return true;
+        }
+
+        let block = wins[0];
+        board
+            .apply_move(block, defender)
+            .expect("block should be legal");
+        let won = continue_from(self, board);
+        board.undo_move(block).expect("undo should be valid");
+
+        won
+    }
+
+    /// Whether `attacker`, if they moved again, could make a double four.
+    fn is_three(&mut self, board: &mut Board, attacker: Player) -> bool {
+        let k = board.k();
+        board.threat_moves(attacker, k - 2).into_iter().any(|m| {
+            board
+                .apply_move(m, attacker)
+                .expect("threat should be legal");
+            let double = board.winning_moves(attacker).len() > 1;
+            board.undo_move(m).expect("undo should be valid");
+            double
+        })
+    }
+
+    /// Whether every reply that could answer `attacker`'s three still loses:
+    /// taking a cell of one of their threatening windows, or counter
+    /// attacking with a four.
+    fn every_reply_loses(&mut self, board: &mut Board, attacker: Player, depth: u32) -> bool {
+        let defender = attacker.next();
+        let k = board.k();
+        let mut replies = board.threat_moves(attacker, k - 2);
+        replies.extend(board.threat_moves(defender, k - 2));
+        replies.sort_unstable();
+        replies.dedup();
+
+        replies.into_iter().all(|r| {
+            board
+                .apply_move(r, defender)
+                .expect("reply should be legal");
+            let won = self.vct(board, attacker, depth).is_some();
+            board.undo_move(r).expect("undo should be valid");
+            won
+        })
+    }
+}
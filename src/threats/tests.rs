@@ -0,0 +1,96 @@
+use std::time::Instant;
+
+use crate::board::{Board, Player};
+use crate::threats::{DEADLINE_CHECK_INTERVAL, ThreatSearch};
+
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_four() {
+        // Two threes of X, each blocked at one end by O, crossing at 40 on a
+        // 9x9 board with k = 5: row 4 at 37 38 39 and column 4 at 13 22 31.
+        // Neither four alone wins, but 40 makes both at once.
+        let b = Board::from_moves(
+            9,
+            5,
+            &[
+                (37, Player::X),
+                (36, Player::O),
+                (38, Player::X),
+                (4, Player::O),
+                (39, Player::X),
+                (80, Player::O),
+                (13, Player::X),
+                (72, Player::O),
+                (22, Player::X),
+                (8, Player::O),
+                (31, Player::X),
+                (0, Player::O),
+            ],
+        );
+
+        let mut search = ThreatSearch::new().vcf_only();
+        assert_eq!(search.find_win(&b, Player::X), Some(40));
+        assert_eq!(search.nodes(), 1);
+        assert_eq!(ThreatSearch::new().find_win(&b, Player::O), None);
+        assert!(ThreatSearch::new().find_threat(&b, Player::O).is_some());
+    }
+
+    #[test]
+    fn double_three_wins_with_threats() {
+        // Open twos of X crossing at 113 on a 15x15 board with k = 5: row 7
+        // at 111 112 and column 8 at 83 98. There are no fours to make, but
+        // 113 makes two open threes and O can only stop one.
+        let b = Board::from_moves(
+            15,
+            5,
+            &[
+                (111, Player::X),
+                (0, Player::O),
+                (112, Player::X),
+                (14, Player::O),
+                (83, Player::X),
+                (210, Player::O),
+                (98, Player::X),
+                (224, Player::O),
+            ],
+        );
+
+        assert_eq!(ThreatSearch::new().vcf_only().find_win(&b, Player::X), None);
+        assert_eq!(ThreatSearch::new().find_win(&b, Player::X), Some(113));
+    }
+
+    #[test]
+    fn no_win_on_a_quiet_board() {
+        let b = Board::from_moves(15, 5, &[(112, Player::X), (113, Player::O)]);
+        let mut search = ThreatSearch::new().with_node_limit(1000);
+
+        assert_eq!(search.find_win(&b, Player::X), None);
+        assert!(search.nodes() <= 1001);
+    }
+
+    #[test]
+    fn gives_up_at_the_deadline() {
+        // The double three from above, which takes a few more positions to
+        // find than pass before the first look at the clock.
+        let b = Board::from_moves(
+            15,
+            5,
+            &[
+                (111, Player::X),
+                (0, Player::O),
+                (112, Player::X),
+                (14, Player::O),
+                (83, Player::X),
+                (210, Player::O),
+                (98, Player::X),
+                (224, Player::O),
+            ],
+        );
+        let mut search = ThreatSearch::new().with_deadline(Instant::now());
+
+        assert_eq!(search.find_win(&b, Player::X), None);
+        assert_eq!(search.nodes(), DEADLINE_CHECK_INTERVAL);
+    }
+}
@@ -0,0 +1,431 @@
+use std::error::Error;
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{PrintStyledContent, StyledContent, Stylize};
+use crossterm::{cursor, execute, queue, terminal};
+
+use crate::board::{Board, Notation, Player};
+use crate::mct_bot::{self, Bot, Candidate, Ponder, Progress, SearchResult};
+
+const TICK: Duration = Duration::from_millis(100);
+const CANDIDATE_ROWS: usize = 5;
+
+struct Search {
+    handle: mct_bot::Search,
+    started: Instant,
+    player: Player,
+    progress: Option<Progress>,
+}
+
+struct Game {
+    start: Board,
+    starting_player: Player,
+    human: Option<Player>,
+    notation: Notation,
+
+    board: Board,
+    player: Player,
+    history: Vec<Board>,
+    moves: Vec<(Player, i16)>,
+    winner: Option<Player>,
+    cursor: (i16, i16),
+
+    bot: Option<Bot>,
+    search: Option<Search>,
+    ponder: Option<Ponder>,
+    pondering: bool,
+    iterations: usize,
+    candidates: Vec<Candidate>,
+    status: String,
+}
+
+/// Restores the terminal however the TUI exits.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Plays a game in a full-screen terminal UI. With `human` set the player
+/// moves a cursor around the board and the bot answers; without it the bot
+/// plays both sides. With `pondering` the bot keeps searching while the
+/// player thinks.
+pub fn run(
+    board: Board,
+    bot: Bot,
+    starting_player: Player,
+    human: Option<Player>,
+    notation: Notation,
+    pondering: bool,
+) -> Result<(), Box<dyn Error>> {
+    let n = board.n();
+    let mut game = Game {
+        start: board.clone(),
+        starting_player,
+        human,
+        notation,
+        board,
+        player: starting_player,
+        history: vec![],
+        moves: vec![],
+        winner: None,
+        cursor: (n / 2, n / 2),
+        bot: Some(bot),
+        search: None,
+        ponder: None,
+        pondering,
+        iterations: 0,
+        candidates: vec![],
+        status: String::new(),
+    };
+
+    terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    let mut stdout = io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    loop {
+        game.poll_search()?;
+        game.start_search();
+        game.start_ponder();
+        game.draw(&mut stdout)?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        let last = n - 1;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Left | KeyCode::Char('h') => game.cursor.0 = (game.cursor.0 - 1).max(0),
+            KeyCode::Right | KeyCode::Char('l') => game.cursor.0 = (game.cursor.0 + 1).min(last),
+            KeyCode::Up | KeyCode::Char('k') => game.cursor.1 = (game.cursor.1 - 1).max(0),
+            KeyCode::Down | KeyCode::Char('j') => game.cursor.1 = (game.cursor.1 + 1).min(last),
+            KeyCode::Enter | KeyCode::Char(' ') => game.human_move(),
+            KeyCode::Char('u') => game.undo(),
+            KeyCode::Char('m') => game.move_now()?,
+            KeyCode::Char('n') => game.new_game(),
+            _ => {}
+        }
+    }
+}
+
+impl Game {
+    fn is_over(&self) -> bool {
+        self.winner.is_some() || self.board.is_tie()
+    }
+
+    fn bot_to_move(&self) -> bool {
+        self.human != Some(self.player)
+    }
+
+    fn play(&mut self, m: i16) -> Result<(), Box<dyn Error>> {
+        let before = self.board.clone();
+        self.winner = self.board.apply_move(m, self.player)?;
+        self.history.push(before);
+        self.moves.push((self.player, m));
+        self.player = self.player.next();
+        Ok(())
+    }
+
+    fn human_move(&mut self) {
+        if self.is_over() || self.bot_to_move() {
+            return;
+        }
+
+        let m = self.cursor.1 * self.board.n() + self.cursor.0;
+        self.status = match self.play(m) {
+            Ok(()) => String::new(),
+            Err(_) => "That cell is taken".to_string(),
+        };
+    }
+
+    fn start_search(&mut self) {
+        if self.search.is_some() || self.is_over() || !self.bot_to_move() {
+            return;
+        }
+
+        self.stop_ponder();
+        let Some(bot) = self.bot.take() else {
+            return;
+        };
+
+        self.search = Some(Search {
+            handle: bot.start_search(&self.board, self.player),
+            started: Instant::now(),
+            player: self.player,
+            progress: None,
+        });
+    }
+
+    fn start_ponder(&mut self) {
+        if !self.pondering
+            || self.human.is_none()
+            || self.search.is_some()
+            || self.is_over()
+            || self.bot_to_move()
+        {
+            return;
+        }
+
+        if let Some(bot) = self.bot.take() {
+            self.ponder = Some(bot.ponder(&self.board, self.player));
+        }
+    }
+
+    fn stop_ponder(&mut self) {
+        if let Some(ponder) = self.ponder.take() {
+            self.bot = Some(ponder.stop());
+        }
+    }
+
+    fn poll_search(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(search) = &mut self.search else {
+            return Ok(());
+        };
+
+        search.progress = search.handle.progress().cloned();
+        if !search.handle.is_finished() {
+            return Ok(());
+        }
+
+        let search = self.search.take().expect("search should be running");
+        self.finish_search(search.handle.join())
+    }
+
+    fn finish_search(&mut self, (bot, m): SearchResult) -> Result<(), Box<dyn Error>> {
+        self.iterations = bot.iterations();
+        self.candidates = bot.candidates();
+        self.bot = Some(bot);
+        self.play(m?)
+    }
+
+    /// Cuts the bot's search short; it plays its best move so far.
+    fn move_now(&mut self) -> Result<(), Box<dyn Error>> {
+        match self.search.take() {
+            Some(search) => self.finish_search(search.handle.stop()),
+            None => Ok(()),
+        }
+    }
+
+    fn undo(&mut self) {
+        if self.search.is_some() {
+            self.status = "Wait for the bot to move".to_string();
+            return;
+        }
+
+        self.stop_ponder();
+        while let Some(board) = self.history.pop() {
+            let (player, _) = self.moves.pop().expect("every snapshot has a move");
+            self.board = board;
+            self.player = player;
+            self.winner = None;
+
+            if !self.bot_to_move() || self.human.is_none() {
+                break;
+            }
+        }
+
+        self.status = String::new();
+    }
+
+    fn new_game(&mut self) {
+        if self.search.is_some() {
+            self.status = "Wait for the bot to move".to_string();
+            return;
+        }
+
+        self.stop_ponder();
+        self.board = self.start.clone();
+        self.player = self.starting_player;
+        self.history.clear();
+        self.moves.clear();
+        self.winner = None;
+        self.status = String::new();
+    }
+
+    fn cell(&self, m: i16) -> StyledContent<String> {
+        let cell = self.board.cells()[m as usize];
+        let (open, close) = if self.board.last_move() == Some(m) {
+            ('(', ')')
+        } else {
+            ('[', ']')
+        };
+        let stone = match cell {
+            Some(p) => p.to_string(),
+            None => " ".to_string(),
+        };
+
+        let content = format!("{open}{stone}{close}");
+        let content = match cell {
+            Some(Player::X) => content.red(),
+            Some(Player::O) => content.green(),
+            None => content.stylize(),
+        };
+
+        let (x, y) = self.cursor;
+        if self.human.is_some() && m == y * self.board.n() + x {
+            content.reverse()
+        } else {
+            content
+        }
+    }
+
+    fn status_line(&self) -> String {
+        if let Some(winner) = self.winner {
+            return format!("{winner} wins. n: new game, u: undo");
+        }
+        if self.board.is_tie() {
+            return "Draw. n: new game, u: undo".to_string();
+        }
+        if let Some(search) = &self.search {
+            let elapsed = search.started.elapsed().as_secs_f32();
+            let status = format!("Bot ({}) thinking {elapsed:.1}s", search.player);
+            return match &search.progress {
+                Some(p) => format!(
+                    "{status}, best {} ({} visits, {} iterations), m: move now",
+                    self.notation.format(p.best_move, self.board.n()),
+                    p.visits,
+                    p.iterations
+                ),
+                None => status,
+            };
+        }
+        if !self.status.is_empty() {
+            return self.status.clone();
+        }
+
+        if self.ponder.is_some() {
+            return format!("{} to move, bot pondering", self.player);
+        }
+
+        format!("{} to move", self.player)
+    }
+
+    fn draw(&self, stdout: &mut Stdout) -> io::Result<()> {
+        let n = self.board.n();
+        let label_width = n.to_string().len() as u16;
+        let panel = label_width + 1 + 3 * n as u16 + 4;
+
+        queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+        queue!(
+            stdout,
+            cursor::MoveTo(0, 0),
+            PrintStyledContent(format!("rs-titato  {n}x{n}, {} in a row", self.board.k()).bold())
+        )?;
+
+        for y in 0..n {
+            queue!(
+                stdout,
+                cursor::MoveTo(0, 2 + y as u16),
+                PrintStyledContent(format!("{:>w$} ", n - y, w = label_width as usize).dark_grey())
+            )?;
+            for x in 0..n {
+                queue!(stdout, PrintStyledContent(self.cell(y * n + x)))?;
+            }
+        }
+
+        queue!(stdout, cursor::MoveTo(label_width + 1, 2 + n as u16))?;
+        for x in 0..n {
+            let column = self.notation_column(x);
+            queue!(
+                stdout,
+                PrintStyledContent(format!("{column:^3}").dark_grey())
+            )?;
+        }
+
+        self.draw_moves(stdout, panel)?;
+
+        let mut row = 4 + n as u16;
+        queue!(
+            stdout,
+            cursor::MoveTo(0, row),
+            PrintStyledContent(self.status_line().bold())
+        )?;
+        row += 2;
+
+        if !self.candidates.is_empty() {
+            queue!(
+                stdout,
+                cursor::MoveTo(0, row),
+                PrintStyledContent(
+                    format!("Last search: {} iterations", self.iterations).stylize()
+                )
+            )?;
+            for c in self.candidates.iter().take(CANDIDATE_ROWS) {
+                row += 1;
+                let m = self.notation.format(c.game_move, n);
+                let line = format!(
+                    "  {m:>5} {:>8} visits {:>5.1}%",
+                    c.visits,
+                    100.0 * c.win_rate()
+                );
+                queue!(
+                    stdout,
+                    cursor::MoveTo(0, row),
+                    PrintStyledContent(line.stylize())
+                )?;
+            }
+            row += 2;
+        }
+
+        let help = "arrows/hjkl: move  enter: place  m: move now  u: undo  n: new game  q: quit";
+        queue!(
+            stdout,
+            cursor::MoveTo(0, row),
+            PrintStyledContent(help.dark_grey())
+        )?;
+
+        stdout.flush()
+    }
+
+    fn draw_moves(&self, stdout: &mut Stdout, column: u16) -> io::Result<()> {
+        let n = self.board.n();
+        queue!(
+            stdout,
+            cursor::MoveTo(column, 2),
+            PrintStyledContent("Moves".bold())
+        )?;
+
+        let rows = (n as usize).max(CANDIDATE_ROWS);
+        let pairs: Vec<&[(Player, i16)]> = self.moves.chunks(2).collect();
+        let skip = pairs.len().saturating_sub(rows);
+        for (i, pair) in pairs.iter().enumerate().skip(skip) {
+            let line = pair
+                .iter()
+                .map(|&(p, m)| format!("{p} {:<5}", self.notation.format(m, n)))
+                .collect::<Vec<String>>()
+                .join(" ");
+            queue!(
+                stdout,
+                cursor::MoveTo(column, 3 + (i - skip) as u16),
+                PrintStyledContent(format!("{:>3}. {line}", i + 1).stylize())
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn notation_column(&self, x: i16) -> String {
+        let n = self.board.n();
+        match self.notation {
+            Notation::Coord => Notation::Coord
+                .format(x, n)
+                .trim_end_matches(|c: char| c.is_ascii_digit())
+                .to_string(),
+            Notation::Index => x.to_string(),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// `rs-titato <args> move`, returning the exit code.
+fn exit_code_with(args: &[&str]) -> Option<i32> {
+    let output = Command::new(env!("CARGO_BIN_EXE_rs-titato"))
+        .args(args)
+        .args(["--think-time", "0.05", "move"])
+        .output()
+        .expect("binary should run");
+
+    output.status.code()
+}
+
+/// `rs-titato <args> move` on a state file holding `state`, returning the
+/// exit code.
+fn exit_code(name: &str, state: &str, args: &[&str]) -> Option<i32> {
+    let path: PathBuf =
+        env::temp_dir().join(format!("rs-titato-{}-{name}.txt", std::process::id()));
+    fs::write(&path, state).expect("state file should be written");
+
+    let path = path.to_str().expect("path should be utf-8");
+    let code = exit_code_with(&[args, &["--state-file", path]].concat());
+    fs::remove_file(path).expect("state file should be removed");
+
+    code
+}
+
+#[test]
+fn valid_state_exits_0() {
+    assert_eq!(exit_code("valid", "<cells>\nX_O______", &[]), Some(0));
+}
+
+#[test]
+fn invalid_cell_exits_3() {
+    assert_eq!(exit_code("cell", "<board>\n[X][ ]\n[ ][Y]", &[]), Some(3));
+    assert_eq!(exit_code("cells", "<cells>\nX_O_Y____", &[]), Some(3));
+}
+
+#[test]
+fn ragged_board_exits_3() {
+    assert_eq!(
+        exit_code("ragged", "<board>\n[X][ ][ ]\n[ ][O]\n[ ][ ][ ][ ]", &[]),
+        Some(3)
+    );
+}
+
+#[test]
+fn oversized_move_list_exits_3() {
+    assert_eq!(exit_code("size", "<moves>\n300\na1", &[]), Some(3));
+    assert_eq!(exit_code("move", "<moves>\n3\n300", &[]), Some(3));
+}
+
+#[test]
+fn oversized_board_exits_4() {
+    assert_eq!(exit_code_with(&["-n", "200"]), Some(4));
+    assert_eq!(exit_code_with(&["-n", "0"]), Some(4));
+}
+
+#[test]
+fn row_length_out_of_range_exits_4() {
+    assert_eq!(exit_code_with(&["-k", "0"]), Some(4));
+    assert_eq!(exit_code_with(&["-n", "3", "-k", "5"]), Some(4));
+    assert_eq!(exit_code_with(&["-n", "15", "-k", "5"]), Some(0));
+}
+
+#[test]
+fn row_longer_than_state_exits_3() {
+    let state = "<cells>\nX_O______";
+    assert_eq!(exit_code("row", state, &["-n", "15", "-k", "3"]), Some(0));
+    assert_eq!(exit_code("long-row", state, &["-k", "5"]), Some(3));
+}